@@ -0,0 +1,202 @@
+use std::fmt::Write as FmtWrite;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+use crate::render::{Render, Renderer};
+
+pub type Palette = fn(char) -> [u8; 3];
+
+pub fn default_palette(c: char) -> [u8; 3] {
+    match c {
+        '.' | ' ' => [0x10, 0x10, 0x10],
+        '#' => [0xc0, 0xc0, 0xc0],
+        'o' => [0xe0, 0xc0, 0x40],
+        '@' => [0xe0, 0x40, 0x40],
+        _ => {
+            let h = (c as u32).wrapping_mul(2654435761);
+            [
+                (h >> 24) as u8 | 0x40,
+                (h >> 16) as u8 | 0x40,
+                (h >> 8) as u8 | 0x40,
+            ]
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Animation {
+    renderer: Renderer,
+    frames: Vec<String>,
+    frame_delay: f64,
+}
+
+impl Default for Animation {
+    fn default() -> Self {
+        Animation {
+            renderer: Renderer::new(),
+            frames: vec![],
+            frame_delay: 0.1,
+        }
+    }
+}
+
+impl Animation {
+    pub fn new(renderer: Renderer) -> Animation {
+        Animation {
+            renderer,
+            ..Animation::default()
+        }
+    }
+
+    pub fn frame_delay(mut self, seconds: f64) -> Animation {
+        self.frame_delay = seconds;
+        self
+    }
+
+    pub fn push<R: Render + ?Sized>(&mut self, src: &R) {
+        self.frames.push(self.renderer.render(src));
+    }
+
+    pub fn push_frame(&mut self, frame: String) {
+        self.frames.push(frame);
+    }
+
+    pub fn frames(&self) -> &[String] {
+        &self.frames
+    }
+
+    fn size(&self) -> (usize, usize) {
+        let width = self
+            .frames
+            .iter()
+            .flat_map(|frame| frame.lines().map(|line| line.chars().count()))
+            .max()
+            .unwrap_or(0);
+        let height = self
+            .frames
+            .iter()
+            .map(|frame| frame.lines().count())
+            .max()
+            .unwrap_or(0);
+        (width, height)
+    }
+
+    pub fn to_asciicast(&self) -> String {
+        let (width, height) = self.size();
+        let mut res = format!("{{\"version\": 2, \"width\": {width}, \"height\": {height}}}\n");
+
+        for (i, frame) in self.frames.iter().enumerate() {
+            let data = format!("\x1b[2J\x1b[H{}", frame.replace('\n', "\r\n"));
+            writeln!(
+                res,
+                "[{:.3}, \"o\", \"{}\"]",
+                i as f64 * self.frame_delay,
+                json_escape(&data)
+            )
+            .unwrap();
+        }
+        res
+    }
+
+    pub fn write_asciicast(&self, file: &str) -> Result<(), String> {
+        fs::write(file, self.to_asciicast()).map_err(|why| why.to_string())
+    }
+
+    pub fn write_ppm_frames(
+        &self,
+        dir: &str,
+        scale: usize,
+        palette: Palette,
+    ) -> Result<(), String> {
+        fs::create_dir_all(dir).map_err(|why| why.to_string())?;
+
+        let (width, height) = self.size();
+        let digits = self.frames.len().max(1).to_string().len();
+
+        for (i, frame) in self.frames.iter().enumerate() {
+            let file = Path::new(dir).join(format!("frame_{i:0digits$}.ppm"));
+            let mut fd = fs::File::create(file).map_err(|why| why.to_string())?;
+            fd.write_all(&to_ppm(frame, width, height, scale, palette))
+                .map_err(|why| why.to_string())?;
+        }
+        Ok(())
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        if path.ends_with(".cast") {
+            self.write_asciicast(path)
+        } else {
+            self.write_ppm_frames(path, 4, default_palette)
+        }
+    }
+}
+
+fn json_escape(s: &str) -> String {
+    let mut res = String::new();
+    for c in s.chars() {
+        match c {
+            '"' => res.push_str("\\\""),
+            '\\' => res.push_str("\\\\"),
+            '\n' => res.push_str("\\n"),
+            '\r' => res.push_str("\\r"),
+            c if (c as u32) < 0x20 => write!(res, "\\u{:04x}", c as u32).unwrap(),
+            c => res.push(c),
+        }
+    }
+    res
+}
+
+fn to_ppm(frame: &str, width: usize, height: usize, scale: usize, palette: Palette) -> Vec<u8> {
+    let rows = frame
+        .lines()
+        .map(|line| line.chars().collect::<Vec<char>>())
+        .collect::<Vec<Vec<char>>>();
+
+    let mut res = format!("P6\n{} {}\n255\n", width * scale, height * scale).into_bytes();
+    for y in 0..height * scale {
+        for x in 0..width * scale {
+            let c = rows
+                .get(y / scale)
+                .and_then(|row| row.get(x / scale))
+                .copied()
+                .unwrap_or(' ');
+            res.extend_from_slice(&palette(c));
+        }
+    }
+    res
+}
+
+#[cfg(test)]
+mod animation_tests {
+    use super::*;
+    use crate::render::Layer;
+
+    #[test]
+    fn test_asciicast() {
+        let mut animation = Animation::default().frame_delay(0.5);
+        animation.push(&Layer::from_iter([((0, 0), '#')]));
+        animation.push(&Layer::from_iter([((0, 0), '#'), ((1, 1), '"')]));
+
+        assert_eq!(
+            animation.to_asciicast(),
+            "{\"version\": 2, \"width\": 2, \"height\": 2}\n\
+             [0.000, \"o\", \"\\u001b[2J\\u001b[H#\\r\\n\"]\n\
+             [0.500, \"o\", \"\\u001b[2J\\u001b[H#.\\r\\n.\\\"\\r\\n\"]\n"
+        );
+    }
+
+    #[test]
+    fn test_ppm() {
+        let ppm = to_ppm("#.\n", 2, 2, 2, default_palette);
+        let header = b"P6\n4 4\n255\n";
+        assert_eq!(&ppm[..header.len()], header);
+        assert_eq!(ppm.len(), header.len() + 4 * 4 * 3);
+        assert_eq!(&ppm[header.len()..header.len() + 3], &default_palette('#'));
+        assert_eq!(
+            &ppm[header.len() + 6..header.len() + 9],
+            &default_palette('.')
+        );
+        assert_eq!(&ppm[ppm.len() - 3..], &default_palette(' '));
+    }
+}
@@ -0,0 +1,145 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    pub start: usize,
+    pub length: usize,
+}
+
+impl Cycle {
+    pub fn new(start: usize, length: usize) -> Cycle {
+        Cycle { start, length }
+    }
+
+    pub fn index_of(&self, step: usize) -> usize {
+        if step < self.start {
+            step
+        } else {
+            self.start + (step - self.start) % self.length
+        }
+    }
+
+    pub fn value_at<'a, T>(&self, values: &'a [T], step: usize) -> &'a T {
+        &values[self.index_of(step)]
+    }
+
+    pub fn extrapolate(&self, values: &[i64], step: usize) -> i64 {
+        if step < values.len() {
+            return values[step];
+        }
+
+        let per_cycle = values[self.start + self.length] - values[self.start];
+        let cycles = ((step - self.start) / self.length) as i64;
+        values[self.index_of(step)] + cycles * per_cycle
+    }
+}
+
+pub fn find_repeat_by<T, K, I, F>(items: I, mut key: F) -> Option<(Cycle, Vec<T>)>
+where
+    I: IntoIterator<Item = T>,
+    K: Hash + Eq,
+    F: FnMut(&T) -> K,
+{
+    let mut seen: HashMap<K, usize> = HashMap::new();
+    let mut history = vec![];
+
+    for (idx, item) in items.into_iter().enumerate() {
+        let prev = seen.insert(key(&item), idx);
+        history.push(item);
+        if let Some(start) = prev {
+            return Some((Cycle::new(start, idx - start), history));
+        }
+    }
+    None
+}
+
+pub fn find_repeat<S, F>(init: S, mut step: F) -> (Cycle, Vec<S>)
+where
+    S: Hash + Eq + Clone,
+    F: FnMut(&S) -> S,
+{
+    let states = std::iter::successors(Some(init), |state| Some(step(state)));
+    find_repeat_by(states, |state| state.clone()).unwrap()
+}
+
+pub fn brent<S, F>(init: S, mut step: F) -> Cycle
+where
+    S: PartialEq + Clone,
+    F: FnMut(&S) -> S,
+{
+    let mut power = 1;
+    let mut length = 1;
+    let mut tortoise = init.clone();
+    let mut hare = step(&init);
+    while tortoise != hare {
+        if power == length {
+            tortoise = hare.clone();
+            power *= 2;
+            length = 0;
+        }
+        hare = step(&hare);
+        length += 1;
+    }
+
+    let mut tortoise = init.clone();
+    let mut hare = init;
+    for _ in 0..length {
+        hare = step(&hare);
+    }
+
+    let mut start = 0;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        start += 1;
+    }
+
+    Cycle::new(start, length)
+}
+
+#[cfg(test)]
+mod cycle_tests {
+    use super::*;
+
+    #[test]
+    fn test_find_repeat_by() {
+        let items = [3, 1, 4, 1, 5, 9, 2, 6];
+        let (cycle, history) = find_repeat_by(items, |x| *x).unwrap();
+        assert_eq!(cycle, Cycle::new(1, 2));
+        assert_eq!(history, vec![3, 1, 4, 1]);
+
+        assert_eq!(find_repeat_by([1, 2, 3], |x| *x), None);
+    }
+
+    #[test]
+    fn test_find_repeat() {
+        let (cycle, history) = find_repeat(0_u32, |x| (x * x + 1) % 255);
+        assert_eq!(cycle, Cycle::new(2, 6));
+        assert_eq!(history, vec![0, 1, 2, 5, 26, 167, 95, 101, 2]);
+        assert_eq!(*cycle.value_at(&history, 1000), 26);
+    }
+
+    #[test]
+    fn test_brent() {
+        assert_eq!(brent(0_u32, |x| (x * x + 1) % 255), Cycle::new(2, 6));
+        assert_eq!(brent(7_u32, |x| (x + 1) % 10), Cycle::new(0, 10));
+        for init in 0..50_u64 {
+            let step = |x: &u64| (x * x + 7) % 1009;
+            assert_eq!(brent(init, step), find_repeat(init, step).0);
+        }
+    }
+
+    #[test]
+    fn test_extrapolate() {
+        // after a prefix of 2 steps the values grow by 5 every 3 steps
+        let values = [0, 1, 2, 4, 6, 7];
+        let cycle = Cycle::new(2, 3);
+        assert_eq!(cycle.index_of(1), 1);
+        assert_eq!(cycle.index_of(8), 2);
+        assert_eq!(cycle.extrapolate(&values, 4), 6);
+        assert_eq!(cycle.extrapolate(&values, 6), 9);
+        assert_eq!(cycle.extrapolate(&values, 8), 12);
+        assert_eq!(cycle.extrapolate(&values, 10), 16);
+    }
+}
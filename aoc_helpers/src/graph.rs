@@ -0,0 +1,251 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use crate::interner::Interner;
+
+pub type NodeId = usize;
+pub type Weight = i64;
+pub type Distances = Vec<Vec<Option<Weight>>>;
+
+#[derive(Debug, Clone, Default)]
+pub struct Graph {
+    names: Interner,
+    edges: Vec<Vec<(NodeId, Weight)>>,
+}
+
+impl Graph {
+    pub fn new() -> Graph {
+        Graph::default()
+    }
+
+    pub fn node(&mut self, label: &str) -> NodeId {
+        let id = self.names.intern(label) as NodeId;
+        if id == self.edges.len() {
+            self.edges.push(vec![]);
+        }
+        id
+    }
+
+    pub fn id(&self, label: &str) -> Option<NodeId> {
+        self.names.get(label).map(|id| id as NodeId)
+    }
+
+    pub fn label(&self, id: NodeId) -> &str {
+        self.names.resolve(id as u32)
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    pub fn nodes(&self) -> std::ops::Range<NodeId> {
+        0..self.len()
+    }
+
+    pub fn add_edge(&mut self, from: NodeId, to: NodeId, weight: Weight) {
+        let edges = &mut self.edges[from];
+        match edges.iter_mut().find(|(id, _)| *id == to) {
+            Some(edge) => edge.1 = edge.1.min(weight),
+            None => edges.push((to, weight)),
+        }
+    }
+
+    pub fn edges(&self, from: NodeId) -> &[(NodeId, Weight)] {
+        &self.edges[from]
+    }
+
+    pub fn floyd_warshall(&self) -> Distances {
+        let mut res = vec![vec![None; self.len()]; self.len()];
+        for from in self.nodes() {
+            res[from][from] = Some(0);
+            for (to, weight) in self.edges(from) {
+                res[from][*to] = Some(res[from][*to].map_or(*weight, |w: Weight| w.min(*weight)));
+            }
+        }
+
+        for k in self.nodes() {
+            for i in self.nodes() {
+                for j in self.nodes() {
+                    if let (Some(ik), Some(kj)) = (res[i][k], res[k][j]) {
+                        if res[i][j].is_none_or(|ij| ik + kj < ij) {
+                            res[i][j] = Some(ik + kj);
+                        }
+                    }
+                }
+            }
+        }
+
+        res
+    }
+
+    pub fn dijkstra(&self, from: NodeId) -> Vec<Option<Weight>> {
+        self.dijkstra_through(from, |_| true)
+    }
+
+    fn dijkstra_through<F: Fn(NodeId) -> bool>(
+        &self,
+        from: NodeId,
+        passable: F,
+    ) -> Vec<Option<Weight>> {
+        let mut res = vec![None; self.len()];
+        let mut jobs = BinaryHeap::new();
+        res[from] = Some(0);
+        jobs.push(Reverse((0, from)));
+
+        while let Some(Reverse((dist, node))) = jobs.pop() {
+            if res[node].is_some_and(|d| d < dist) || (node != from && !passable(node)) {
+                continue;
+            }
+
+            for (next, weight) in self.edges(node) {
+                let next_dist = dist + weight;
+                if res[*next].is_none_or(|d| next_dist < d) {
+                    res[*next] = Some(next_dist);
+                    jobs.push(Reverse((next_dist, *next)));
+                }
+            }
+        }
+
+        res
+    }
+
+    pub fn johnson(&self) -> Option<Distances> {
+        // Bellman-Ford from a virtual node connected to every node with weight 0
+        let mut potential = vec![0; self.len()];
+        for round in 0..=self.len() {
+            let mut changed = false;
+            for from in self.nodes() {
+                for (to, weight) in self.edges(from) {
+                    if potential[from] + weight < potential[*to] {
+                        potential[*to] = potential[from] + weight;
+                        changed = true;
+                    }
+                }
+            }
+            if !changed {
+                break;
+            }
+            if round == self.len() {
+                return None;
+            }
+        }
+
+        let mut reweighted = self.clone();
+        for from in self.nodes() {
+            for edge in reweighted.edges[from].iter_mut() {
+                edge.1 += potential[from] - potential[edge.0];
+            }
+        }
+
+        Some(
+            self.nodes()
+                .map(|from| {
+                    reweighted
+                        .dijkstra(from)
+                        .into_iter()
+                        .enumerate()
+                        .map(|(to, dist)| dist.map(|d| d - potential[from] + potential[to]))
+                        .collect()
+                })
+                .collect(),
+        )
+    }
+
+    pub fn contract<F: Fn(NodeId, &str) -> bool>(&self, remove: F) -> Graph {
+        let removed = self
+            .nodes()
+            .map(|id| remove(id, self.label(id)))
+            .collect::<Vec<bool>>();
+
+        let mut res = Graph::new();
+        for id in self.nodes().filter(|id| !removed[*id]) {
+            res.node(self.label(id));
+        }
+
+        for from in self.nodes().filter(|id| !removed[*id]) {
+            let dists = self.dijkstra_through(from, |id| removed[id]);
+            for (to, dist) in dists.into_iter().enumerate() {
+                if to == from || removed[to] {
+                    continue;
+                }
+                if let Some(dist) = dist {
+                    let (res_from, res_to) = (res.node(self.label(from)), res.node(self.label(to)));
+                    res.add_edge(res_from, res_to, dist);
+                }
+            }
+        }
+
+        res
+    }
+}
+
+#[cfg(test)]
+mod graph_tests {
+    use super::*;
+
+    fn graph(edges: &[(&str, &str, Weight)]) -> Graph {
+        let mut res = Graph::new();
+        for (from, to, weight) in edges {
+            let (from, to) = (res.node(from), res.node(to));
+            res.add_edge(from, to, *weight);
+        }
+        res
+    }
+
+    #[test]
+    fn test_nodes() {
+        let mut g = Graph::new();
+        assert_eq!(g.node("AA"), 0);
+        assert_eq!(g.node("BB"), 1);
+        assert_eq!(g.node("AA"), 0);
+        assert_eq!(g.id("BB"), Some(1));
+        assert_eq!(g.id("CC"), None);
+        assert_eq!(g.label(1), "BB");
+
+        g.add_edge(0, 1, 5);
+        g.add_edge(0, 1, 3);
+        assert_eq!(g.edges(0), &[(1, 3)]);
+    }
+
+    #[test]
+    fn test_shortest_paths() {
+        let g = graph(&[("a", "b", 4), ("a", "c", 1), ("c", "b", 2), ("b", "d", 1)]);
+        let fw = g.floyd_warshall();
+        assert_eq!(fw[0], vec![Some(0), Some(3), Some(1), Some(4)]);
+        assert_eq!(fw[3], vec![None, None, None, Some(0)]);
+        assert_eq!(g.dijkstra(0), fw[0]);
+        assert_eq!(g.johnson(), Some(fw));
+    }
+
+    #[test]
+    fn test_johnson_negative() {
+        let g = graph(&[("a", "b", 2), ("b", "c", -3), ("a", "c", 1)]);
+        assert_eq!(g.johnson(), Some(g.floyd_warshall()));
+        assert_eq!(g.johnson().unwrap()[0][2], Some(-1));
+
+        let g = graph(&[("a", "b", 1), ("b", "a", -2)]);
+        assert_eq!(g.johnson(), None);
+    }
+
+    #[test]
+    fn test_contract() {
+        let g = graph(&[
+            ("AA", "BB", 1),
+            ("BB", "AA", 1),
+            ("BB", "CC", 1),
+            ("CC", "BB", 1),
+            ("CC", "DD", 1),
+            ("DD", "CC", 1),
+            ("AA", "DD", 5),
+        ]);
+        let c = g.contract(|_, label| label == "BB" || label == "CC");
+        assert_eq!(c.len(), 2);
+        let (aa, dd) = (c.id("AA").unwrap(), c.id("DD").unwrap());
+        assert_eq!(c.edges(aa), &[(dd, 3)]);
+        assert_eq!(c.edges(dd), &[(aa, 3)]);
+    }
+}
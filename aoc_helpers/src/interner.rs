@@ -0,0 +1,83 @@
+use std::collections::HashMap;
+
+pub type Id = u32;
+
+#[derive(Debug, Clone, Default)]
+pub struct Interner {
+    labels: Vec<String>,
+    ids: HashMap<String, Id>,
+}
+
+impl Interner {
+    pub fn new() -> Interner {
+        Interner::default()
+    }
+
+    pub fn intern(&mut self, label: &str) -> Id {
+        if let Some(id) = self.ids.get(label) {
+            return *id;
+        }
+
+        let id = self.labels.len() as Id;
+        self.labels.push(label.to_string());
+        self.ids.insert(label.to_string(), id);
+        id
+    }
+
+    pub fn get(&self, label: &str) -> Option<Id> {
+        self.ids.get(label).copied()
+    }
+
+    pub fn resolve(&self, id: Id) -> &str {
+        &self.labels[id as usize]
+    }
+
+    pub fn len(&self) -> usize {
+        self.labels.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.labels.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Id, &str)> {
+        self.labels
+            .iter()
+            .enumerate()
+            .map(|(id, label)| (id as Id, label.as_str()))
+    }
+}
+
+impl<'a> FromIterator<&'a str> for Interner {
+    fn from_iter<I: IntoIterator<Item = &'a str>>(iter: I) -> Self {
+        let mut res = Interner::new();
+        for label in iter {
+            res.intern(label);
+        }
+        res
+    }
+}
+
+#[cfg(test)]
+mod interner_tests {
+    use super::*;
+
+    #[test]
+    fn test_interner() {
+        let mut names = Interner::new();
+        assert!(names.is_empty());
+        assert_eq!(names.intern("root"), 0);
+        assert_eq!(names.intern("humn"), 1);
+        assert_eq!(names.intern("root"), 0);
+        assert_eq!(names.len(), 2);
+        assert_eq!(names.get("humn"), Some(1));
+        assert_eq!(names.get("pppw"), None);
+        assert_eq!(names.resolve(1), "humn");
+
+        let names = Interner::from_iter(["AA", "BB", "AA", "LONGNAME"]);
+        assert_eq!(
+            names.iter().collect::<Vec<(Id, &str)>>(),
+            vec![(0, "AA"), (1, "BB"), (2, "LONGNAME")]
+        );
+    }
+}
@@ -1,4 +1,13 @@
+pub mod animation;
+pub mod cycle;
 pub mod data_loader;
+pub mod graph;
+pub mod interner;
+pub mod logging;
 pub mod matrix_helper;
+pub mod parallel;
 pub mod range;
+pub mod rational;
+pub mod render;
 pub mod slide_iter;
+pub mod snapshot;
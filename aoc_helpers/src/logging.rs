@@ -0,0 +1,70 @@
+use std::env;
+use std::str::FromStr;
+
+use tracing::Level;
+
+fn level_from_verbosity(verbosity: usize) -> Option<Level> {
+    match verbosity {
+        0 => None,
+        1 => Some(Level::INFO),
+        2 => Some(Level::DEBUG),
+        _ => Some(Level::TRACE),
+    }
+}
+
+fn verbosity<I: Iterator<Item = String>>(args: I) -> usize {
+    args.filter_map(|arg| match arg.as_str() {
+        "--verbose" => Some(1),
+        a if a.starts_with('-') && a.len() > 1 && a[1..].chars().all(|c| c == 'v') => {
+            Some(a.len() - 1)
+        }
+        _ => None,
+    })
+    .sum()
+}
+
+pub fn positional_args() -> Vec<String> {
+    env::args()
+        .skip(1)
+        .filter(|arg| !arg.starts_with('-'))
+        .collect()
+}
+
+pub fn init() {
+    let level = env::var("AOC_LOG")
+        .ok()
+        .and_then(|lvl| Level::from_str(&lvl).ok())
+        .or_else(|| level_from_verbosity(verbosity(env::args().skip(1))));
+
+    if let Some(level) = level {
+        let _ = tracing_subscriber::fmt()
+            .with_max_level(level)
+            .with_writer(std::io::stderr)
+            .try_init();
+    }
+}
+
+#[cfg(test)]
+mod logging_tests {
+    use super::*;
+
+    fn args(a: &[&str]) -> impl Iterator<Item = String> {
+        a.iter()
+            .map(|s| s.to_string())
+            .collect::<Vec<String>>()
+            .into_iter()
+    }
+
+    #[test]
+    fn test_verbosity() {
+        assert_eq!(verbosity(args(&[])), 0);
+        assert_eq!(verbosity(args(&["out.cast"])), 0);
+        assert_eq!(verbosity(args(&["-v"])), 1);
+        assert_eq!(verbosity(args(&["-vv", "out.cast"])), 2);
+        assert_eq!(verbosity(args(&["-v", "--verbose", "-v"])), 3);
+        assert_eq!(verbosity(args(&["-x", "-"])), 0);
+        assert_eq!(level_from_verbosity(0), None);
+        assert_eq!(level_from_verbosity(2), Some(Level::DEBUG));
+        assert_eq!(level_from_verbosity(5), Some(Level::TRACE));
+    }
+}
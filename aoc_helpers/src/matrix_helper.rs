@@ -42,12 +42,3 @@ pub fn get_flatten_matrix<'a, T>(mat: &'a Matrix<T>) -> Vec<(usize, usize, &'a T
 
     res
 }
-
-pub fn print_matrix<T: std::fmt::Display>(mat: &Matrix<T>) {
-    for row in 0..mat.rows() {
-        for col in 0..mat.cols() {
-            print!("{} ", mat.get(row, col).unwrap());
-        }
-        println!();
-    }
-}
@@ -0,0 +1,156 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Mul, Neg, Sub};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rational {
+    num: i128,
+    den: i128,
+}
+
+fn gcd(a: i128, b: i128) -> i128 {
+    if b == 0 {
+        a.abs()
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+impl Rational {
+    pub fn new(num: i128, den: i128) -> Rational {
+        assert!(den != 0, "denominator must not be zero");
+        let div = gcd(num, den) * den.signum();
+        Rational {
+            num: num / div,
+            den: den / div,
+        }
+    }
+
+    pub fn zero() -> Rational {
+        Rational::from(0)
+    }
+
+    pub fn one() -> Rational {
+        Rational::from(1)
+    }
+
+    pub fn num(&self) -> i128 {
+        self.num
+    }
+
+    pub fn den(&self) -> i128 {
+        self.den
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.num == 0
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.den == 1
+    }
+
+    pub fn to_integer(&self) -> Option<i128> {
+        self.is_integer().then_some(self.num)
+    }
+
+    pub fn checked_div(self, rhs: Rational) -> Option<Rational> {
+        (!rhs.is_zero()).then(|| Rational::new(self.num * rhs.den, self.den * rhs.num))
+    }
+}
+
+impl From<i64> for Rational {
+    fn from(val: i64) -> Self {
+        Rational {
+            num: val as i128,
+            den: 1,
+        }
+    }
+}
+
+impl Add for Rational {
+    type Output = Rational;
+    fn add(self, rhs: Rational) -> Rational {
+        Rational::new(self.num * rhs.den + rhs.num * self.den, self.den * rhs.den)
+    }
+}
+
+impl Sub for Rational {
+    type Output = Rational;
+    fn sub(self, rhs: Rational) -> Rational {
+        self + -rhs
+    }
+}
+
+impl Mul for Rational {
+    type Output = Rational;
+    fn mul(self, rhs: Rational) -> Rational {
+        Rational::new(self.num * rhs.num, self.den * rhs.den)
+    }
+}
+
+impl Neg for Rational {
+    type Output = Rational;
+    fn neg(self) -> Rational {
+        Rational {
+            num: -self.num,
+            den: self.den,
+        }
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Rational {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.num * other.den).cmp(&(other.num * self.den))
+    }
+}
+
+impl fmt::Display for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_integer() {
+            write!(f, "{}", self.num)
+        } else {
+            write!(f, "{}/{}", self.num, self.den)
+        }
+    }
+}
+
+#[cfg(test)]
+mod rational_tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(Rational::new(4, 6), Rational::new(2, 3));
+        assert_eq!(Rational::new(2, -4), Rational::new(-1, 2));
+        assert_eq!(Rational::new(0, -5), Rational::zero());
+        assert_eq!(Rational::new(-3, -6).to_string(), "1/2");
+        assert_eq!(Rational::new(6, 3).to_integer(), Some(2));
+        assert_eq!(Rational::new(7, 3).to_integer(), None);
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let (a, b) = (Rational::new(1, 2), Rational::new(1, 3));
+        assert_eq!(a + b, Rational::new(5, 6));
+        assert_eq!(a - b, Rational::new(1, 6));
+        assert_eq!(a * b, Rational::new(1, 6));
+        assert_eq!(a.checked_div(b), Some(Rational::new(3, 2)));
+        assert_eq!(a.checked_div(Rational::zero()), None);
+        assert_eq!(-a, Rational::new(-1, 2));
+        assert!(b < a && -a < b);
+        assert_eq!(
+            Rational::from(7)
+                .checked_div(Rational::from(2))
+                .unwrap()
+                .to_string(),
+            "7/2"
+        );
+    }
+}
@@ -0,0 +1,282 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use simple_matrix::Matrix;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Viewport {
+    pub x_min: i64,
+    pub y_min: i64,
+    pub x_max: i64,
+    pub y_max: i64,
+}
+
+impl Viewport {
+    pub fn new(x_min: i64, y_min: i64, x_max: i64, y_max: i64) -> Viewport {
+        Viewport {
+            x_min,
+            y_min,
+            x_max,
+            y_max,
+        }
+    }
+
+    pub fn from_points<I: IntoIterator<Item = (i64, i64)>>(points: I) -> Option<Viewport> {
+        points.into_iter().fold(None, |acc, (x, y)| {
+            let point = Viewport::new(x, y, x, y);
+            Some(acc.map_or(point, |vp: Viewport| vp.union(&point)))
+        })
+    }
+
+    pub fn union(&self, other: &Viewport) -> Viewport {
+        Viewport {
+            x_min: self.x_min.min(other.x_min),
+            y_min: self.y_min.min(other.y_min),
+            x_max: self.x_max.max(other.x_max),
+            y_max: self.y_max.max(other.y_max),
+        }
+    }
+
+    pub fn contains(&self, x: i64, y: i64) -> bool {
+        (self.x_min..=self.x_max).contains(&x) && (self.y_min..=self.y_max).contains(&y)
+    }
+
+    pub fn width(&self) -> usize {
+        (1 + self.x_max - self.x_min).max(0) as usize
+    }
+
+    pub fn height(&self) -> usize {
+        (1 + self.y_max - self.y_min).max(0) as usize
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Orientation {
+    #[default]
+    YDown,
+    YUp,
+}
+
+pub trait Render {
+    fn viewport(&self) -> Option<Viewport>;
+
+    fn cell(&self, x: i64, y: i64) -> char;
+
+    fn render(&self) -> String {
+        Renderer::new().render(self)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Layer {
+    cells: HashMap<(i64, i64), char>,
+}
+
+impl Layer {
+    pub fn new() -> Layer {
+        Layer::default()
+    }
+
+    pub fn set(&mut self, x: i64, y: i64, c: char) {
+        self.cells.insert((x, y), c);
+    }
+
+    pub fn get(&self, x: i64, y: i64) -> Option<char> {
+        self.cells.get(&(x, y)).copied()
+    }
+}
+
+impl FromIterator<((i64, i64), char)> for Layer {
+    fn from_iter<I: IntoIterator<Item = ((i64, i64), char)>>(iter: I) -> Self {
+        Layer {
+            cells: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl Render for Layer {
+    fn viewport(&self) -> Option<Viewport> {
+        Viewport::from_points(self.cells.keys().copied())
+    }
+
+    fn cell(&self, x: i64, y: i64) -> char {
+        self.get(x, y).unwrap_or('.')
+    }
+}
+
+impl<T: Clone + Into<char>> Render for Matrix<T> {
+    fn viewport(&self) -> Option<Viewport> {
+        if self.rows() == 0 || self.cols() == 0 {
+            return None;
+        }
+        Some(Viewport::new(
+            0,
+            0,
+            self.cols() as i64 - 1,
+            self.rows() as i64 - 1,
+        ))
+    }
+
+    fn cell(&self, x: i64, y: i64) -> char {
+        self.get(y as usize, x as usize)
+            .map_or(' ', |val| val.clone().into())
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Renderer {
+    viewport: Option<Viewport>,
+    orientation: Orientation,
+    axes: bool,
+    layers: Vec<Layer>,
+}
+
+impl Renderer {
+    pub fn new() -> Renderer {
+        Renderer::default()
+    }
+
+    pub fn viewport(mut self, viewport: Viewport) -> Renderer {
+        self.viewport = Some(viewport);
+        self
+    }
+
+    pub fn orientation(mut self, orientation: Orientation) -> Renderer {
+        self.orientation = orientation;
+        self
+    }
+
+    pub fn axes(mut self, axes: bool) -> Renderer {
+        self.axes = axes;
+        self
+    }
+
+    pub fn layer(mut self, layer: Layer) -> Renderer {
+        self.layers.push(layer);
+        self
+    }
+
+    pub fn render<R: Render + ?Sized>(&self, src: &R) -> String {
+        let viewport = match self.viewport.or_else(|| self.default_viewport(src)) {
+            Some(vp) => vp,
+            None => return String::new(),
+        };
+
+        let ys: Vec<i64> = match self.orientation {
+            Orientation::YDown => (viewport.y_min..=viewport.y_max).collect(),
+            Orientation::YUp => (viewport.y_min..=viewport.y_max).rev().collect(),
+        };
+
+        let label_width = if self.axes {
+            max_label_len(viewport.y_min, viewport.y_max)
+        } else {
+            0
+        };
+
+        let mut res = String::new();
+        if self.axes {
+            res.push_str(&x_axis(&viewport, label_width + 1));
+        }
+
+        for y in ys {
+            if self.axes {
+                write!(res, "{y:>label_width$} ").unwrap();
+            }
+            for x in viewport.x_min..=viewport.x_max {
+                res.push(self.cell(src, x, y));
+            }
+            res.push('\n');
+        }
+
+        res
+    }
+
+    fn default_viewport<R: Render + ?Sized>(&self, src: &R) -> Option<Viewport> {
+        self.layers
+            .iter()
+            .filter_map(|layer| layer.viewport())
+            .chain(src.viewport())
+            .reduce(|acc, vp| acc.union(&vp))
+    }
+
+    fn cell<R: Render + ?Sized>(&self, src: &R, x: i64, y: i64) -> char {
+        self.layers
+            .iter()
+            .rev()
+            .find_map(|layer| layer.get(x, y))
+            .unwrap_or_else(|| src.cell(x, y))
+    }
+}
+
+fn max_label_len(min: i64, max: i64) -> usize {
+    min.to_string().len().max(max.to_string().len())
+}
+
+fn x_axis(viewport: &Viewport, indent: usize) -> String {
+    let labels = (viewport.x_min..=viewport.x_max)
+        .map(|x| x.to_string())
+        .collect::<Vec<String>>();
+    let lines = max_label_len(viewport.x_min, viewport.x_max);
+
+    let mut res = String::new();
+    for line in 0..lines {
+        res.push_str(&" ".repeat(indent));
+        for label in labels.iter() {
+            let pad = lines - label.len();
+            let c = if line < pad {
+                ' '
+            } else {
+                label.chars().nth(line - pad).unwrap()
+            };
+            res.push(c);
+        }
+        res.push('\n');
+    }
+    res
+}
+
+#[cfg(test)]
+mod render_tests {
+    use super::*;
+
+    #[test]
+    fn test_render_matrix() {
+        let mat = Matrix::from_iter(2, 3, "#..##.".chars());
+        assert_eq!(mat.render(), "#..\n##.\n");
+        assert_eq!(
+            Renderer::new().orientation(Orientation::YUp).render(&mat),
+            "##.\n#..\n"
+        );
+    }
+
+    #[test]
+    fn test_render_layers() {
+        let points = Layer::from_iter([((0, 0), '#'), ((2, 1), '#')]);
+        assert_eq!(points.render(), "#..\n..#\n");
+
+        let overlay = Layer::from_iter([((1, 0), '@'), ((0, -1), 'H')]);
+        assert_eq!(
+            Renderer::new().layer(overlay).render(&points),
+            "H..\n#@.\n..#\n"
+        );
+    }
+
+    #[test]
+    fn test_render_viewport_and_axes() {
+        let points = Layer::from_iter([((-1, 0), '#'), ((10, 2), '#')]);
+        assert_eq!(
+            Renderer::new()
+                .viewport(Viewport::new(9, 0, 11, 2))
+                .render(&points),
+            "...\n...\n.#.\n"
+        );
+        assert_eq!(
+            Renderer::new()
+                .viewport(Viewport::new(9, 1, 11, 2))
+                .axes(true)
+                .render(&points),
+            "   11\n  901\n1 ...\n2 .#.\n"
+        );
+        assert_eq!(Layer::new().render(), "");
+    }
+}
@@ -0,0 +1,114 @@
+use std::env;
+use std::fs;
+
+use crate::data_loader::DataLoader;
+
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[32m";
+const RESET: &str = "\x1b[0m";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum DiffLine<'a> {
+    Same(&'a str),
+    Expected(&'a str),
+    Actual(&'a str),
+}
+
+fn diff_lines<'a>(expected: &[&'a str], actual: &[&'a str]) -> Vec<DiffLine<'a>> {
+    let (n, m) = (expected.len(), actual.len());
+    let mut lcs = vec![vec![0_usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if expected[i] == actual[j] {
+                1 + lcs[i + 1][j + 1]
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut res = vec![];
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && expected[i] == actual[j] {
+            res.push(DiffLine::Same(expected[i]));
+            i += 1;
+            j += 1;
+        } else if j == m || (i < n && lcs[i + 1][j] >= lcs[i][j + 1]) {
+            res.push(DiffLine::Expected(expected[i]));
+            i += 1;
+        } else {
+            res.push(DiffLine::Actual(actual[j]));
+            j += 1;
+        }
+    }
+    res
+}
+
+fn colored_diff(diff: &[DiffLine]) -> String {
+    diff.iter()
+        .map(|line| match line {
+            DiffLine::Same(l) => format!("  {l}\n"),
+            DiffLine::Expected(l) => format!("{RED}- {l}{RESET}\n"),
+            DiffLine::Actual(l) => format!("{GREEN}+ {l}{RESET}\n"),
+        })
+        .collect()
+}
+
+pub fn check_snapshot(actual: &str, fixture: &str) -> Result<(), String> {
+    if env::var("UPDATE_SNAPSHOTS").is_ok() {
+        return fs::write(fixture, actual).map_err(|why| why.to_string());
+    }
+
+    let loader = DataLoader::from_file(fixture);
+    let expected = loader
+        .iter()
+        .map(|line| line.as_str())
+        .collect::<Vec<&str>>();
+    let actual = actual.lines().collect::<Vec<&str>>();
+
+    let diff = diff_lines(&expected, &actual);
+    if diff.iter().all(|line| matches!(line, DiffLine::Same(_))) {
+        Ok(())
+    } else {
+        Err(colored_diff(&diff))
+    }
+}
+
+#[track_caller]
+pub fn assert_snapshot(actual: &str, fixture: &str) {
+    if let Err(diff) = check_snapshot(actual, fixture) {
+        panic!("snapshot {fixture} does not match ({RED}-expected{RESET}, {GREEN}+actual{RESET}):\n{diff}");
+    }
+}
+
+#[cfg(test)]
+mod snapshot_tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_lines() {
+        assert_eq!(
+            diff_lines(&["a", "b", "c"], &["a", "x", "c"]),
+            vec![
+                DiffLine::Same("a"),
+                DiffLine::Expected("b"),
+                DiffLine::Actual("x"),
+                DiffLine::Same("c"),
+            ]
+        );
+        assert_eq!(
+            diff_lines(&["a"], &["a", "b"]),
+            vec![DiffLine::Same("a"), DiffLine::Actual("b")]
+        );
+        assert_eq!(diff_lines(&[], &[]), vec![]);
+    }
+
+    #[test]
+    fn test_colored_diff() {
+        assert_eq!(
+            colored_diff(&diff_lines(&["#.", ".."], &["#.", ".#"])),
+            "  #.\n\x1b[31m- ..\x1b[0m\n\x1b[32m+ .#\x1b[0m\n"
+        );
+    }
+}
@@ -1,6 +1,5 @@
 use aoc_helpers::data_loader::DataLoader;
-use simple_matrix::Matrix;
-use std::iter;
+use aoc_helpers::render::{Orientation, Render, Renderer, Viewport};
 
 use std::collections::HashSet;
 
@@ -101,27 +100,40 @@ impl World {
     }
 }
 
-fn print_map(max_row: usize, max_col: usize, world: &World) {
-    //let max_row = world.tail_track.iter().map(|pos| pos.y).max().unwrap() as usize;
-    //let max_col = world.tail_track.iter().map(|pos| pos.y).max().unwrap() as usize;
-
-    let mut mat: Matrix<char> = Matrix::from_iter(max_row + 1, max_col + 1, iter::repeat('.'));
-    for pos in world.tail_track.iter() {
-        mat.set(pos.y as usize, pos.x as usize, '#');
+impl Render for World {
+    fn viewport(&self) -> Option<Viewport> {
+        Viewport::from_points(
+            self.tail_track
+                .iter()
+                .chain([self.head, self.tail].iter())
+                .map(|pos| (pos.x as i64, pos.y as i64)),
+        )
     }
 
-    mat.set(0, 0, 's');
-    mat.set(world.tail.y as usize, world.tail.x as usize, 'T');
-    mat.set(world.head.y as usize, world.head.x as usize, 'H');
-
-    for row_idx in (0..max_row).rev() {
-        let row = mat.get_row(row_idx).unwrap();
-        for c in row {
-            print!("{}", c);
+    fn cell(&self, x: i64, y: i64) -> char {
+        let pos = Pos {
+            x: x as i32,
+            y: y as i32,
+        };
+        if pos == self.head {
+            'H'
+        } else if pos == self.tail {
+            'T'
+        } else if pos == Pos::new() {
+            's'
+        } else if self.tail_track.contains(&pos) {
+            '#'
+        } else {
+            '.'
         }
-        println!("");
     }
-    println!("");
+}
+
+fn print_map(world: &World) {
+    println!(
+        "{}",
+        Renderer::new().orientation(Orientation::YUp).render(world)
+    );
 }
 
 fn get_cmds(loader: &DataLoader) -> Result<Vec<(Step, i32)>, &str> {
@@ -148,7 +160,7 @@ fn run_cmds(cmds: &Vec<(Step, i32)>, world: &mut World) {
             world.follow_with_tail();
         }
         //println!("World: {:?}", world);
-        //print_map(10, 10, &world);
+        //print_map(world);
         //println!("");
     }
 }
@@ -1,5 +1,5 @@
 use aoc_helpers::data_loader::DataLoader;
-use simple_matrix::Matrix;
+use aoc_helpers::render::{Orientation, Render, Renderer, Viewport};
 use std::iter;
 
 use std::collections::HashSet;
@@ -233,45 +233,40 @@ impl World {
     }
 }
 
-fn print_map(max_row: usize, max_col: usize, world: &World) {
-    //let max_row = world.tail_track.iter().map(|pos| pos.y).max().unwrap() as usize;
-    //let max_col = world.tail_track.iter().map(|pos| pos.y).max().unwrap() as usize;
-
-    let col_offset : i32 = 12;
-    let row_offset :i32= 6;
-
-    let mut mat: Matrix<char> = Matrix::from_iter(max_row + 1, max_col + 1, iter::repeat('.'));
-    for pos in world.tail_track.iter() {
-        mat.set(
-            (pos.y + row_offset) as usize,
-            (pos.x + col_offset) as usize,
-            '#',
-        );
-    }
-
-    mat.set((0 + row_offset) as usize, (0 + col_offset) as usize, 's');
-    for (i, pos) in world.knots.iter().enumerate().rev() {
-        let val;
-        match i {
-            0 => val = 'H',
-            9 => val = 'T',
-            _ => val = i.to_string().chars().next().unwrap(),
-        }
-        mat.set(
-            (pos.y + row_offset) as usize,
-            (pos.x + col_offset) as usize,
-            val,
-        );
+impl Render for World {
+    fn viewport(&self) -> Option<Viewport> {
+        Viewport::from_points(
+            self.tail_track
+                .iter()
+                .chain(self.knots.iter())
+                .map(|pos| (pos.x as i64, pos.y as i64)),
+        )
     }
 
-    for row_idx in (0..max_row).rev() {
-        let row = mat.get_row(row_idx).unwrap();
-        for c in row {
-            print!("{}", c);
+    fn cell(&self, x: i64, y: i64) -> char {
+        let pos = Pos {
+            x: x as i32,
+            y: y as i32,
+        };
+        match self.knots.iter().position(|knot| *knot == pos) {
+            Some(0) => 'H',
+            Some(9) => 'T',
+            Some(i) => i.to_string().chars().next().unwrap(),
+            None if pos == Pos::new() => 's',
+            None if self.tail_track.contains(&pos) => '#',
+            None => '.',
         }
-        println!("");
     }
-    println!("");
+}
+
+fn print_map(world: &World) {
+    println!(
+        "{}",
+        Renderer::new()
+            .orientation(Orientation::YUp)
+            .axes(true)
+            .render(world)
+    );
 }
 
 fn get_cmds(loader: &DataLoader) -> Result<Vec<(Step, i32)>, &str> {
@@ -304,9 +299,9 @@ fn run_cmds_debug(cmds: &Vec<(Step, i32)>, world: &mut World) {
         for i in 0..*count {
             //println!("next cmd: {:?}; count: {}/{}", cmd, i, count);
             world.move_knots(*cmd);
-            //print_map(21, 26, &world);
+            //print_map(world);
         }
-        print_map(22, 27, &world);
+        print_map(world);
         //println!("World: {:?}", world);
         println!("");
     }
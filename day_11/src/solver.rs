@@ -6,6 +6,7 @@ use std::rc::Rc;
 
 use itertools::Itertools;
 use std::collections::VecDeque;
+use tracing::{debug, trace};
 
 #[derive(Clone)]
 struct Monkey {
@@ -170,11 +171,17 @@ fn exec_one_monkey_round(monkeys: &mut Vec<Monkey>) {
         monkeys[i].throw_count += monkeys[i].items.len();
         monkeys[i].items.clear();
         while let Some(item) = current.items.pop_front() {
-            //println!("  - Monkey inspects an item with a worry level of {item}");
+            trace!(
+                "Monkey {} inspects an item with a worry level of {item}",
+                current.id
+            );
             let worry_lvl = current.op.as_ref()(item) / 3;
-            //println!("  -- Monkey gets bored with item. Worry level chagnes to {worry_lvl}");
+            trace!(
+                "Monkey {} gets bored with item. Worry level changes to {worry_lvl}",
+                current.id
+            );
             let next = current.next_monkey.as_ref()(worry_lvl);
-            //println!("  -- Item is thrown to monkey {next}");
+            trace!("Item is thrown to monkey {next}");
             monkeys[next].items.push_back(worry_lvl);
         }
     }
@@ -186,11 +193,13 @@ fn exec_one_monkey_round_no_div(monkeys: &mut Vec<Monkey>, mod_factor: i128) {
         monkeys[i].throw_count += monkeys[i].items.len();
         monkeys[i].items.clear();
         while let Some(item) = current.items.pop_front() {
-            //println!("  - Monkey inspects an item with a worry level of {item}");
+            trace!(
+                "Monkey {} inspects an item with a worry level of {item}",
+                current.id
+            );
             let worry_lvl = current.op.as_ref()(item);
-            //println!("  -- Monkey gets bored with item. Worry level chagnes to {worry_lvl}");
             let next = current.next_monkey.as_ref()(worry_lvl);
-            //println!("  -- Item is thrown to monkey {next}");
+            trace!("Item with worry level {worry_lvl} is thrown to monkey {next}");
             monkeys[next].items.push_back(worry_lvl % mod_factor);
         }
     }
@@ -200,8 +209,8 @@ pub fn solve_a(loader: &DataLoader) -> Result<String, &str> {
     let mut monkeys = parse_monkeys(loader)?;
     for round in 1..=20 {
         exec_one_monkey_round(&mut monkeys);
-        //println!("-------- After round {} ------------", round);
-        //monkeys.iter().for_each(|monkey| println!("{monkey}\n"));
+        debug!("After round {round}");
+        monkeys.iter().for_each(|monkey| debug!("{monkey}"));
     }
 
     Ok(monkeys
@@ -216,15 +225,14 @@ pub fn solve_b(loader: &DataLoader) -> Result<String, &str> {
     let mut monkeys = parse_monkeys(loader)?;
     let mod_factor = monkeys.iter().map(|m| m.div_by).product();
 
-    println!("mod_factor={mod_factor}");
+    debug!("mod_factor={mod_factor}");
 
     for round in 1..=10000 {
         exec_one_monkey_round_no_div(&mut monkeys, mod_factor);
-        //println!("-------- After round {} ------------", round);
-        //monkeys.iter().for_each(|monkey| println!("{monkey}\n"));
+        trace!("After round {round}");
     }
 
-    monkeys.iter().for_each(|monkey| println!("{monkey}\n"));
+    monkeys.iter().for_each(|monkey| debug!("{monkey}"));
 
     Ok(monkeys
         .iter()
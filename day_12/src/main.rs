@@ -1,14 +1,16 @@
 #![allow(unused_variables, dead_code)]
 
 use aoc_helpers::data_loader::DataLoader;
+use aoc_helpers::parallel;
 
 mod solver;
 use crate::solver::{solve_a, solve_b};
 
 fn main() {
     let a_input = DataLoader::from_file("data/puzzle_input.txt");
-    println!("a: {}", solve_a(&a_input).unwrap());
-    println!("b: {}", solve_b(&a_input).unwrap());
+    let (a, b) = parallel::join(|| solve_a(&a_input), || solve_b(&a_input));
+    println!("a: {}", a.unwrap());
+    println!("b: {}", b.unwrap());
 }
 
 #[cfg(test)]
@@ -1,7 +1,9 @@
 use aoc_helpers::data_loader::DataLoader;
-use aoc_helpers::matrix_helper::{get_flatten_matrix, print_matrix, MatrixLoader};
+use aoc_helpers::matrix_helper::{get_flatten_matrix, MatrixLoader};
 use comparator::collections::BinaryHeap;
 use comparator::comparing;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 use simple_matrix::Matrix;
 use std::iter;
 
@@ -115,12 +117,19 @@ pub fn solve_b(loader: &DataLoader) -> Result<String, &str> {
     let end = *find_poses('E', &mat).first().ok_or("End not found")?;
     mat.set(end.row(), end.col(), 'z');
 
-    let mut res : Vec<u64> = vec![];
-    for start in starts {
-        let res_mat = calc_shortest_path(start, &mat);
-        let res_val = *res_mat.get(end.row(), end.col()).unwrap();
-        res.push(res_val);
-    }
-
-    Ok(res.iter().min().unwrap().to_string())
+    #[cfg(not(feature = "parallel"))]
+    let starts = starts.into_iter();
+    #[cfg(feature = "parallel")]
+    let starts = starts.into_par_iter();
+
+    let res = starts
+        .map(|start| {
+            *calc_shortest_path(start, &mat)
+                .get(end.row(), end.col())
+                .unwrap()
+        })
+        .min()
+        .ok_or("No start found")?;
+
+    Ok(res.to_string())
 }
@@ -123,7 +123,7 @@ fn to_lrs(loader: &DataLoader) -> Result<Vec<(Item, Item)>, &str> {
         .chunks(3)
         .into_iter()
         .map(|mut iter| {
-            if let Some(l_str) = iter.next() && let Some(r_str) = iter.next() {
+            if let (Some(l_str), Some(r_str)) = (iter.next(), iter.next()) {
                 match (Item::try_from(&mut l_str.chars()), Item::try_from(&mut r_str.chars())) {
                     (Ok(left), Ok(right)) => Ok((left,right)),
                     (Err(err), _) | (_, Err(err)) => Err(err),
@@ -2,21 +2,30 @@
 #![allow(unused_variables, dead_code)]
 
 use aoc_helpers::data_loader::DataLoader;
+use aoc_helpers::logging;
 
 mod solver_a;
-use crate::solver_a::solve_a;
+use crate::solver_a::{animate_a, solve_a};
 mod solver_b;
 use crate::solver_b::solve_b;
 
 fn main() {
+    logging::init();
+
     let a_input = DataLoader::from_file("data/puzzle_input.txt");
     println!("a: {}", solve_a(&a_input).unwrap());
     println!("b: {}", solve_b(&a_input).unwrap());
+
+    if let Some(path) = logging::positional_args().first() {
+        animate_a(&a_input).unwrap().save(path).unwrap();
+    }
 }
 
 #[cfg(test)]
 mod test_main {
     use super::*;
+    use crate::solver_a::render_a;
+    use aoc_helpers::snapshot::assert_snapshot;
 
     #[test]
     fn a_test() {
@@ -33,4 +42,25 @@ mod test_main {
             DataLoader::from_file("data/b_test_result.txt").test_result()
         );
     }
+
+    #[test]
+    fn a_snapshot_test() {
+        let loader = DataLoader::from_file("data/test_input.txt");
+        for drops in [1, 5, 22, 24] {
+            assert_snapshot(
+                &render_a(&loader, drops).unwrap(),
+                &format!("data/a_test_snapshot_{drops}.txt"),
+            );
+        }
+    }
+
+    #[test]
+    fn a_animation_test() {
+        let animation = animate_a(&DataLoader::from_file("data/test_input.txt")).unwrap();
+        assert_eq!(animation.frames().len(), 25);
+        assert_snapshot(
+            animation.frames().last().unwrap(),
+            "data/a_test_snapshot_24.txt",
+        );
+    }
 }
@@ -1,12 +1,14 @@
+use aoc_helpers::animation::Animation;
 use aoc_helpers::data_loader::DataLoader;
 use aoc_helpers::matrix_helper::get_flatten_matrix;
+use aoc_helpers::render::{Render, Renderer, Viewport};
 use aoc_helpers::slide_iter::SlideIterator;
 use itertools::{Itertools, MinMaxResult};
 use simple_matrix::Matrix;
 use std::cmp::{max, min};
 use std::fmt;
 
-#[derive(Default, PartialEq)]
+#[derive(Default, Clone, Copy, PartialEq)]
 enum Obj {
     #[default]
     Air,
@@ -43,26 +45,36 @@ impl Point {
     }
 }
 
+impl From<Obj> for char {
+    fn from(obj: Obj) -> char {
+        match obj {
+            Obj::Air => '.',
+            Obj::Wall => '#',
+            Obj::Sand => 'o',
+            Obj::SandEntry => '+',
+        }
+    }
+}
+
 impl fmt::Display for Obj {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            Obj::Air => write!(f, "."),
-            Obj::Wall => write!(f, "#"),
-            Obj::Sand => write!(f, "o"),
-            Obj::SandEntry => write!(f, "+"),
-        }
+        write!(f, "{}", char::from(*self))
+    }
+}
+
+impl Render for Cave {
+    fn viewport(&self) -> Option<Viewport> {
+        self.map.viewport()
+    }
+
+    fn cell(&self, x: i64, y: i64) -> char {
+        self.map.cell(x, y)
     }
 }
 
 impl fmt::Display for Cave {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        for y in 0..self.map.rows() {
-            for x in 0..self.map.cols() {
-                write!(f, "{}", self.map.get(y, x).unwrap())?;
-            }
-            writeln!(f, "")?;
-        }
-        writeln!(f, "")
+        writeln!(f, "{}", self.render())
     }
 }
 
@@ -225,8 +237,32 @@ impl Cave {
     }
 }
 
+fn fill_with_sand<F: FnMut(&Cave)>(cave: &mut Cave, mut on_step: F) {
+    while cave.drop_sand() {
+        on_step(cave);
+    }
+}
+
 pub fn solve_a(loader: &DataLoader) -> Result<String, &str> {
     let mut cave = to_cave(loader)?;
-    while cave.drop_sand() {}
+    fill_with_sand(&mut cave, |_| ());
     Ok(cave.count(Obj::Sand).to_string())
 }
+
+pub fn render_a(loader: &DataLoader, drops: usize) -> Result<String, &str> {
+    let mut cave = to_cave(loader)?;
+    for _ in 0..drops {
+        if !cave.drop_sand() {
+            break;
+        }
+    }
+    Ok(cave.render())
+}
+
+pub fn animate_a(loader: &DataLoader) -> Result<Animation, &str> {
+    let mut cave = to_cave(loader)?;
+    let mut animation = Animation::new(Renderer::new());
+    animation.push(&cave);
+    fill_with_sand(&mut cave, |c| animation.push(c));
+    Ok(animation)
+}
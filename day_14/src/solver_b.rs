@@ -1,12 +1,13 @@
 use aoc_helpers::data_loader::DataLoader;
 use aoc_helpers::matrix_helper::get_flatten_matrix;
+use aoc_helpers::render::{Render, Viewport};
 use aoc_helpers::slide_iter::SlideIterator;
 use itertools::{Itertools, MinMaxResult};
 use simple_matrix::Matrix;
 use std::cmp::{max, min};
 use std::fmt;
 
-#[derive(Default, PartialEq)]
+#[derive(Default, Clone, Copy, PartialEq)]
 enum Obj {
     #[default]
     Air,
@@ -43,26 +44,36 @@ impl Point {
     }
 }
 
+impl From<Obj> for char {
+    fn from(obj: Obj) -> char {
+        match obj {
+            Obj::Air => '.',
+            Obj::Wall => '#',
+            Obj::Sand => 'o',
+            Obj::SandEntry => '+',
+        }
+    }
+}
+
 impl fmt::Display for Obj {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            Obj::Air => write!(f, "."),
-            Obj::Wall => write!(f, "#"),
-            Obj::Sand => write!(f, "o"),
-            Obj::SandEntry => write!(f, "+"),
-        }
+        write!(f, "{}", char::from(*self))
+    }
+}
+
+impl Render for Cave {
+    fn viewport(&self) -> Option<Viewport> {
+        self.map.viewport()
+    }
+
+    fn cell(&self, x: i64, y: i64) -> char {
+        self.map.cell(x, y)
     }
 }
 
 impl fmt::Display for Cave {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        for y in 0..self.map.rows() {
-            for x in 0..self.map.cols() {
-                write!(f, "{}", self.map.get(y, x).unwrap())?;
-            }
-            writeln!(f, "")?;
-        }
-        writeln!(f, "")
+        writeln!(f, "{}", self.render())
     }
 }
 
@@ -219,7 +230,7 @@ impl Cave {
             Some(Point(x, y)) => {
                 self.map.set(y, x, Obj::Sand);
                 Point::new(x, y) != self.sand_entry
-            },
+            }
             None => panic!("sand should never fall into the void"),
         }
     }
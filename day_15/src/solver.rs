@@ -1,6 +1,8 @@
 use aoc_helpers::data_loader::DataLoader;
 use aoc_helpers::slide_iter::SlideIterator;
 use itertools::{Itertools, Position};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 use std::cmp::{max, min};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
@@ -176,22 +178,30 @@ pub fn solve_a(loader: &DataLoader, row: i64) -> Result<String, &str> {
 
 pub fn solve_b(loader: &DataLoader, max_xy: i64) -> Result<String, &str> {
     let points = get_points(loader);
-    let mut inverted_super_ranges = vec![];
-    for row in 0..=max_xy {
-        let mut super_range = points
-            .iter()
-            .map(|ps| get_range_on_row(ps, row))
-            .flatten()
-            .fold(SuperRange::new(), |mut acc, range| {
-                acc.merge(range);
-                acc
-            });
-        super_range.trim(Range::new(0, max_xy));
-        if super_range.range_counts() > 1 {
-            inverted_super_ranges.push((row, super_range.get_inverted_super_range().unwrap()));
-        }
-    }
-    let (y, sr)= inverted_super_ranges.first().unwrap();
+
+    #[cfg(not(feature = "parallel"))]
+    let rows = 0..=max_xy;
+    #[cfg(feature = "parallel")]
+    let rows = (0..=max_xy).into_par_iter();
+
+    let (y, sr) = rows
+        .filter_map(|row| {
+            let mut super_range = points.iter().flat_map(|ps| get_range_on_row(ps, row)).fold(
+                SuperRange::new(),
+                |mut acc, range| {
+                    acc.merge(range);
+                    acc
+                },
+            );
+            super_range.trim(Range::new(0, max_xy));
+            if super_range.range_counts() > 1 {
+                Some((row, super_range.get_inverted_super_range().unwrap()))
+            } else {
+                None
+            }
+        })
+        .min_by_key(|(row, _)| *row)
+        .ok_or("No free position found")?;
     let x = sr.0.first().unwrap().0;
     Ok((x * 4000000 + y).to_string())
 }
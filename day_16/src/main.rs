@@ -2,15 +2,25 @@
 #![allow(unused_variables, dead_code)]
 
 use aoc_helpers::data_loader::DataLoader;
+use aoc_helpers::logging;
+use std::env;
 
 mod solver_a;
-use crate::solver_a::solve_a;
+use crate::solver_a::{plan_a, solve_a};
 
 mod solver_b;
-use crate::solver_b::solve_b;
+use crate::solver_b::{plan_b, solve_b};
 
 fn main() {
+    logging::init();
+
     let a_input = DataLoader::from_file("data/puzzle_input.txt");
+    if env::args().any(|arg| arg == "--plan") {
+        println!("a:\n{}", plan_a(&a_input).unwrap());
+        println!("b:\n{}", plan_b(&a_input).unwrap());
+        return;
+    }
+
     //println!("a: {}", solve_a(&a_input).unwrap());
     println!("b: {}", solve_b(&a_input).unwrap());
 }
@@ -1,41 +1,40 @@
 use aoc_helpers::data_loader::DataLoader;
+use aoc_helpers::graph::{Graph, Weight};
+use aoc_helpers::interner::{Id, Interner};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 use std::collections::HashMap;
-use std::fmt;
+use tracing::{debug, info};
 
-#[derive(Clone, Copy, Hash, PartialEq, Eq)]
-struct Valve(u8, u8);
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+struct Valve(Id);
 
 impl Valve {
-    fn from(raw_valve: &str) -> Valve {
-        Valve(
-            raw_valve.chars().nth(0).unwrap() as u8,
-            raw_valve.chars().nth(1).unwrap() as u8,
-        )
+    fn from(raw_valve: &str, names: &Interner) -> Valve {
+        Valve(names.get(raw_valve).expect("unknown valve"))
     }
 
-    fn from_line(line: &str) -> Valve {
-        line.split(" ")
-            .enumerate()
-            .filter_map(|(i, s)| match i {
-                1 => Some(Valve::from(s)),
-                _ => None,
-            })
-            .nth(0)
-            .unwrap()
+    fn from_line(line: &str, names: &Interner) -> Valve {
+        Valve::from(line.split(' ').nth(1).unwrap(), names)
     }
-}
 
-impl fmt::Debug for Valve {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}{}", self.0 as char, self.1 as char)
+    fn name(self, names: &Interner) -> &str {
+        names.resolve(self.0)
     }
 }
 
+fn valve_names(loader: &DataLoader) -> Interner {
+    loader
+        .iter()
+        .map(|line| line.split(' ').nth(1).unwrap())
+        .collect::<Interner>()
+}
+
 #[derive(Debug, Clone)]
 struct ToValves(Vec<(Valve, usize)>);
 
 impl ToValves {
-    fn from_line(line: &String) -> ToValves {
+    fn from_line(line: &str, names: &Interner) -> ToValves {
         ToValves(
             line.split_once("; ")
                 .unwrap()
@@ -43,7 +42,7 @@ impl ToValves {
                 .replace("s", "")
                 .replace("tunnel lead to valve ", "")
                 .split(',')
-                .map(|s| (Valve::from(s.trim()), 1))
+                .map(|s| (Valve::from(s.trim(), names), 1))
                 .collect::<Vec<(Valve, usize)>>(),
         )
     }
@@ -83,6 +82,38 @@ impl History {
             .sum::<u64>();
     }
 
+    fn flow_at(&self, rates: &ValveRates, minute: usize) -> u64 {
+        self.opened_at_min
+            .iter()
+            .filter(|(_, opened)| **opened < minute)
+            .map(|(v, _)| rates.0.get(v).unwrap())
+            .sum()
+    }
+
+    fn plan(&self, names: &Interner, rates: &ValveRates, max_time: usize) -> Vec<String> {
+        let mut steps = vec![];
+        for action in self.actions.iter() {
+            match action {
+                Action::GoTo(v, c) => {
+                    for _ in 0..*c {
+                        steps.push(format!("move to {}", v.name(names)));
+                    }
+                }
+                Action::Open(v) => steps.push(format!("open {}", v.name(names))),
+            }
+        }
+
+        (1..=max_time)
+            .map(|minute| {
+                let step = steps.get(minute - 1).map_or("wait", |s| s.as_str());
+                format!(
+                    "minute {minute}: {step}, flow {}",
+                    self.flow_at(rates, minute)
+                )
+            })
+            .collect()
+    }
+
     fn get_current_time(&self) -> usize {
         self.actions
             .iter()
@@ -99,7 +130,7 @@ impl History {
 struct ValveRates(HashMap<Valve, u64>);
 
 impl ValveRates {
-    fn from(loader: &DataLoader) -> ValveRates {
+    fn from(loader: &DataLoader, names: &Interner) -> ValveRates {
         ValveRates(
             loader
                 .iter()
@@ -116,7 +147,7 @@ impl ValveRates {
                         })
                         .collect::<Vec<&str>>()
                 })
-                .map(|v| (Valve::from(v[0]), v[1].parse::<u64>().unwrap()))
+                .map(|v| (Valve::from(v[0], names), v[1].parse::<u64>().unwrap()))
                 .collect::<HashMap<Valve, u64>>(),
         )
     }
@@ -126,51 +157,53 @@ impl ValveRates {
 struct CaveMap(HashMap<Valve, ToValves>);
 
 impl CaveMap {
-    fn from(loader: &DataLoader) -> CaveMap {
+    fn from(loader: &DataLoader, names: &Interner) -> CaveMap {
         CaveMap(
             loader
                 .iter()
-                .map(|line| (Valve::from_line(line), ToValves::from_line(line)))
+                .map(|line| {
+                    (
+                        Valve::from_line(line, names),
+                        ToValves::from_line(line, names),
+                    )
+                })
                 .collect::<HashMap<Valve, ToValves>>(),
         )
     }
 
-    fn shrinked_map(&self, rates: &ValveRates) -> CaveMap {
-        let mut res = self.clone();
-        for useless_valve in rates.0.iter().filter_map(|(v, r)| {
-            if r == &0 && *v != Valve::from("AA") {
-                Some(v)
-            } else {
-                None
+    fn to_graph(&self, names: &Interner) -> Graph {
+        let mut res = Graph::new();
+        for (from, to_valves) in self.0.iter() {
+            let from = res.node(from.name(names));
+            for (to, cost) in to_valves.0.iter() {
+                let to = res.node(to.name(names));
+                res.add_edge(from, to, *cost as Weight);
             }
-        }) {
-            CaveMap::remove_useless_valve(&mut res, useless_valve);
         }
-
         res
     }
 
-    fn remove_useless_valve(map: &mut CaveMap, valve: &Valve) {
-        let replacements = map
-            .0
-            .remove(valve)
-            .unwrap()
-            .0
-            .iter()
-            .filter(|(v, _)| v != valve)
-            .cloned()
-            .collect::<Vec<(Valve, usize)>>();
-
-        for (_, vs) in map.0.iter_mut() {
-            if let Some((idx, cost)) =
-                vs.0.iter()
-                    .enumerate()
-                    .find_map(|(i, (v, c))| if v == valve { Some((i, *c)) } else { None })
-            {
-                vs.0.remove(idx);
-                vs.0.extend(replacements.iter().map(|(v, c)| (*v, c + cost)));
-            }
-        }
+    fn from_graph(graph: &Graph, names: &Interner) -> CaveMap {
+        CaveMap(
+            graph
+                .nodes()
+                .map(|id| {
+                    let to_valves = graph
+                        .edges(id)
+                        .iter()
+                        .map(|(to, cost)| (Valve::from(graph.label(*to), names), *cost as usize))
+                        .collect::<Vec<(Valve, usize)>>();
+                    (Valve::from(graph.label(id), names), ToValves(to_valves))
+                })
+                .collect::<HashMap<Valve, ToValves>>(),
+        )
+    }
+
+    fn shrinked_map(&self, rates: &ValveRates, names: &Interner) -> CaveMap {
+        let useless =
+            |label: &str| label != "AA" && rates.0.get(&Valve::from(label, names)) == Some(&0);
+        let graph = self.to_graph(names).contract(|_, label| useless(label));
+        CaveMap::from_graph(&graph, names)
     }
 
     fn removed_loopes(&self) -> CaveMap {
@@ -217,11 +250,12 @@ fn calc_optimal_route_impl(
 
     //println!("current_pos={:?}", current_pos);
     // calc best result
-    map.0
-        .get(current_pos)
-        .unwrap()
-        .0
-        .iter()
+    #[cfg(not(feature = "parallel"))]
+    let next_valves = map.0.get(current_pos).unwrap().0.iter();
+    #[cfg(feature = "parallel")]
+    let next_valves = map.0.get(current_pos).unwrap().0.par_iter();
+
+    next_valves
         .map(|(next_valve, cost)| {
             let mut tmp_hist = hist.clone();
             tmp_hist.actions.push(Action::GoTo(*next_valve, *cost));
@@ -231,32 +265,38 @@ fn calc_optimal_route_impl(
         .unwrap()
 }
 
-pub fn solve_a(loader: &DataLoader) -> Result<String, &str> {
-    let map = CaveMap::from(loader);
-    println!("Map={:?}\n", map);
-    let rates = ValveRates::from(loader);
-    println!("Map={:?}\n", rates);
-    let shrinked_map = map.shrinked_map(&rates);
-    println!("shrinked map={:?}\n", shrinked_map);
+fn best_history(loader: &DataLoader) -> (History, Interner, ValveRates) {
+    let names = valve_names(loader);
+    let map = CaveMap::from(loader, &names);
+    debug!("map={:?}", map);
+    let rates = ValveRates::from(loader, &names);
+    debug!("rates={:?}", rates);
+    let shrinked_map = map.shrinked_map(&rates, &names);
+    debug!("shrinked map={:?}", shrinked_map);
     let shrinked_map = shrinked_map.removed_loopes();
-    println!("no loop map={:?}\n", shrinked_map);
-    let start_valve = Valve::from("AA");
+    debug!("no loop map={:?}", shrinked_map);
+    let start_valve = Valve::from("AA", &names);
     //let history = calc_optimal_route(&map, &rates, 30, &start_valve);
     let history = calc_optimal_route(&shrinked_map, &rates, 30, &start_valve);
-    println!("History={:?}\n", history);
-    println!(
-        "open order={:?}\n",
-        history
-            .actions
-            .iter()
-            .filter(|a| if let Action::Open(_) = a { true } else { false })
-            .cloned()
-            .collect::<Vec<Action>>()
-    );
+    debug!("history={:?}", history);
+
+    (history, names, rates)
+}
+
+pub fn solve_a(loader: &DataLoader) -> Result<String, &str> {
+    let (history, names, rates) = best_history(loader);
+    for line in history.plan(&names, &rates, 30) {
+        info!("{}", line);
+    }
 
     Ok(history.current_flow.to_string())
 }
 
+pub fn plan_a(loader: &DataLoader) -> Result<String, &str> {
+    let (history, names, rates) = best_history(loader);
+    Ok(history.plan(&names, &rates, 30).join("\n"))
+}
+
 #[cfg(test)]
 mod test_main {
     use super::*;
@@ -264,32 +304,62 @@ mod test_main {
     #[test]
     fn calc_origin() {
         let loader = DataLoader::from_file("data/test_input.txt");
-        let rates = ValveRates::from(&loader);
+        let names = valve_names(&loader);
+        let rates = ValveRates::from(&loader, &names);
         let mut hist = History::new();
 
-        hist.opened_at_min.insert(Valve::from("DD"), 2);
-        hist.opened_at_min.insert(Valve::from("BB"), 5);
-        hist.opened_at_min.insert(Valve::from("JJ"), 9);
-        hist.opened_at_min.insert(Valve::from("HH"), 17);
-        hist.opened_at_min.insert(Valve::from("EE"), 21);
-        hist.opened_at_min.insert(Valve::from("CC"), 24);
+        hist.opened_at_min.insert(Valve::from("DD", &names), 2);
+        hist.opened_at_min.insert(Valve::from("BB", &names), 5);
+        hist.opened_at_min.insert(Valve::from("JJ", &names), 9);
+        hist.opened_at_min.insert(Valve::from("HH", &names), 17);
+        hist.opened_at_min.insert(Valve::from("EE", &names), 21);
+        hist.opened_at_min.insert(Valve::from("CC", &names), 24);
 
         hist.update_current_flow(&rates, 30);
         assert_eq!(hist.current_flow, 1651);
     }
 
+    #[test]
+    fn plan_test() {
+        let loader = DataLoader::from_file("data/test_input.txt");
+        let names = valve_names(&loader);
+        let rates = ValveRates::from(&loader, &names);
+        let (dd, bb) = (Valve::from("DD", &names), Valve::from("BB", &names));
+        let mut hist = History::new();
+
+        hist.actions = vec![Action::GoTo(dd, 1), Action::Open(dd), Action::GoTo(bb, 2)];
+        hist.opened_at_min.insert(dd, 2);
+        hist.actions.push(Action::Open(bb));
+        hist.opened_at_min.insert(bb, 5);
+
+        let plan = hist.plan(&names, &rates, 30);
+        assert_eq!(plan.len(), 30);
+        assert_eq!(
+            plan[..6],
+            [
+                "minute 1: move to DD, flow 0",
+                "minute 2: open DD, flow 0",
+                "minute 3: move to BB, flow 20",
+                "minute 4: move to BB, flow 20",
+                "minute 5: open BB, flow 20",
+                "minute 6: wait, flow 33",
+            ]
+        );
+    }
+
     #[test]
     fn calc_first_try() {
         let loader = DataLoader::from_file("data/test_input.txt");
-        let rates = ValveRates::from(&loader);
+        let names = valve_names(&loader);
+        let rates = ValveRates::from(&loader, &names);
         let mut hist = History::new();
 
-        hist.opened_at_min.insert(Valve::from("DD"), 2);
-        hist.opened_at_min.insert(Valve::from("BB"), 5);
-        hist.opened_at_min.insert(Valve::from("JJ"), 9);
-        hist.opened_at_min.insert(Valve::from("EE"), 14);
-        hist.opened_at_min.insert(Valve::from("HH"), 18);
-        hist.opened_at_min.insert(Valve::from("CC"), 24);
+        hist.opened_at_min.insert(Valve::from("DD", &names), 2);
+        hist.opened_at_min.insert(Valve::from("BB", &names), 5);
+        hist.opened_at_min.insert(Valve::from("JJ", &names), 9);
+        hist.opened_at_min.insert(Valve::from("EE", &names), 14);
+        hist.opened_at_min.insert(Valve::from("HH", &names), 18);
+        hist.opened_at_min.insert(Valve::from("CC", &names), 24);
 
         hist.update_current_flow(&rates, 30);
         assert_eq!(hist.current_flow, 1650);
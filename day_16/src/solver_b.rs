@@ -1,42 +1,44 @@
 use aoc_helpers::data_loader::DataLoader;
+use aoc_helpers::graph::{Graph, Weight};
+use aoc_helpers::interner::{Id, Interner};
 use itertools::Itertools;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use std::collections::hash_map::Entry;
 use std::collections::HashMap;
-use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use tracing::{debug, info};
 
-#[derive(Clone, Copy, Hash, PartialEq, Eq)]
-struct Valve(u8, u8);
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+struct Valve(Id);
 
 impl Valve {
-    fn from(raw_valve: &str) -> Valve {
-        Valve(
-            raw_valve.chars().nth(0).unwrap() as u8,
-            raw_valve.chars().nth(1).unwrap() as u8,
-        )
+    fn from(raw_valve: &str, names: &Interner) -> Valve {
+        Valve(names.get(raw_valve).expect("unknown valve"))
     }
 
-    fn from_line(line: &str) -> Valve {
-        line.split(" ")
-            .enumerate()
-            .filter_map(|(i, s)| match i {
-                1 => Some(Valve::from(s)),
-                _ => None,
-            })
-            .nth(0)
-            .unwrap()
+    fn from_line(line: &str, names: &Interner) -> Valve {
+        Valve::from(line.split(' ').nth(1).unwrap(), names)
     }
-}
 
-impl fmt::Debug for Valve {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}{}", self.0 as char, self.1 as char)
+    fn name(self, names: &Interner) -> &str {
+        names.resolve(self.0)
     }
 }
 
+fn valve_names(loader: &DataLoader) -> Interner {
+    loader
+        .iter()
+        .map(|line| line.split(' ').nth(1).unwrap())
+        .collect::<Interner>()
+}
+
 #[derive(Debug, Clone)]
 struct ToValves(Vec<(Valve, usize)>);
 
 impl ToValves {
-    fn from_line(line: &String) -> ToValves {
+    fn from_line(line: &str, names: &Interner) -> ToValves {
         ToValves(
             line.split_once("; ")
                 .unwrap()
@@ -44,7 +46,7 @@ impl ToValves {
                 .replace("s", "")
                 .replace("tunnel lead to valve ", "")
                 .split(',')
-                .map(|s| (Valve::from(s.trim()), 1))
+                .map(|s| (Valve::from(s.trim(), names), 1))
                 .collect::<Vec<(Valve, usize)>>(),
         )
     }
@@ -70,28 +72,107 @@ struct History {
 }
 
 impl History {
-    fn new() -> History {
+    fn new(start: Valve) -> History {
         History {
             actions_a: vec![],
             actions_b: vec![],
             opened_at_min: HashMap::new(),
             current_flow: 0,
             current_time: 0,
-            pos_a: Valve::from("AA"),
-            pos_b: Valve::from("AA"),
+            pos_a: start,
+            pos_b: start,
         }
     }
 
     fn update_current_flow(&mut self, rates: &ValveRates, current_time: usize) {
-        self.current_flow = self
-            .opened_at_min
+        self.current_flow = self.total_flow(rates, current_time);
+    }
+
+    fn total_flow(&self, rates: &ValveRates, current_time: usize) -> u64 {
+        self.opened_at_min
             .iter()
             .filter(|(v, opened)| opened < &&current_time)
             .map(|(v, opened)| {
                 let rate = rates.0.get(v).unwrap();
                 ((current_time - opened) as u64) * rate
             })
+            .sum::<u64>()
+    }
+
+    // flow if the closed valves were opened as early as possible, either two
+    // at a time with one move in between or each by the closest agent
+    fn upper_bound(&self, ctx: &CalcContext) -> u64 {
+        let closed = ctx
+            .rates
+            .0
+            .iter()
+            .filter(|(v, rate)| **rate > 0 && !self.opened_at_min.contains_key(v))
+            .map(|(v, rate)| (*v, *rate))
+            .sorted_by_key(|(_, rate)| std::cmp::Reverse(*rate))
+            .collect::<Vec<(Valve, u64)>>();
+        let value = |rate: u64, minute: usize| rate * ctx.max_time.saturating_sub(minute) as u64;
+
+        let paired = closed
+            .iter()
+            .enumerate()
+            .map(|(i, (_, rate))| value(*rate, self.current_time + 2 * (i / 2)))
             .sum::<u64>();
+
+        let (rem_a, rem_b) = (travel_left(&self.actions_a), travel_left(&self.actions_b));
+        let closest = closed
+            .iter()
+            .map(|(v, rate)| {
+                let minute =
+                    (rem_a + ctx.dist(self.pos_a, *v)).min(rem_b + ctx.dist(self.pos_b, *v));
+                value(*rate, self.current_time + minute)
+            })
+            .sum::<u64>();
+
+        self.total_flow(&ctx.rates, ctx.max_time) + paired.min(closest)
+    }
+
+    // everything the rest of the search depends on, with both agents in a
+    // fixed order since they are interchangeable
+    fn state(&self, ctx: &CalcContext) -> State {
+        let opened = self
+            .opened_at_min
+            .keys()
+            .fold(0, |acc, v| acc | ctx.valve_bits[v]);
+        let a = (self.pos_a, travel_left(&self.actions_a));
+        let b = (self.pos_b, travel_left(&self.actions_b));
+        let (a, b) = if (a.0 .0, a.1) <= (b.0 .0, b.1) {
+            (a, b)
+        } else {
+            (b, a)
+        };
+        (self.current_time, a, b, opened)
+    }
+
+    fn flow_at(&self, rates: &ValveRates, minute: usize) -> u64 {
+        self.opened_at_min
+            .iter()
+            .filter(|(_, opened)| **opened < minute)
+            .map(|(v, _)| rates.0.get(v).unwrap())
+            .sum()
+    }
+
+    fn plan(&self, names: &Interner, rates: &ValveRates, max_time: usize) -> Vec<String> {
+        let step = |actions: &Vec<Action>, minute: usize| match actions.get(minute - 1) {
+            Some(Action::GoTo(v, _)) => format!("move to {}", v.name(names)),
+            Some(Action::Open(v)) => format!("open {}", v.name(names)),
+            None => "wait".to_string(),
+        };
+
+        (1..=max_time)
+            .map(|minute| {
+                format!(
+                    "minute {minute}: you {}, elephant {}, flow {}",
+                    step(&self.actions_a, minute),
+                    step(&self.actions_b, minute),
+                    self.flow_at(rates, minute)
+                )
+            })
+            .collect()
     }
 }
 
@@ -99,7 +180,7 @@ impl History {
 struct ValveRates(HashMap<Valve, u64>);
 
 impl ValveRates {
-    fn from(loader: &DataLoader) -> ValveRates {
+    fn from(loader: &DataLoader, names: &Interner) -> ValveRates {
         ValveRates(
             loader
                 .iter()
@@ -116,7 +197,7 @@ impl ValveRates {
                         })
                         .collect::<Vec<&str>>()
                 })
-                .map(|v| (Valve::from(v[0]), v[1].parse::<u64>().unwrap()))
+                .map(|v| (Valve::from(v[0], names), v[1].parse::<u64>().unwrap()))
                 .collect::<HashMap<Valve, u64>>(),
         )
     }
@@ -126,51 +207,53 @@ impl ValveRates {
 struct CaveMap(HashMap<Valve, ToValves>);
 
 impl CaveMap {
-    fn from(loader: &DataLoader) -> CaveMap {
+    fn from(loader: &DataLoader, names: &Interner) -> CaveMap {
         CaveMap(
             loader
                 .iter()
-                .map(|line| (Valve::from_line(line), ToValves::from_line(line)))
+                .map(|line| {
+                    (
+                        Valve::from_line(line, names),
+                        ToValves::from_line(line, names),
+                    )
+                })
                 .collect::<HashMap<Valve, ToValves>>(),
         )
     }
 
-    fn shrinked_map(&self, rates: &ValveRates) -> CaveMap {
-        let mut res = self.clone();
-        for useless_valve in rates.0.iter().filter_map(|(v, r)| {
-            if r == &0 && *v != Valve::from("AA") {
-                Some(v)
-            } else {
-                None
+    fn to_graph(&self, names: &Interner) -> Graph {
+        let mut res = Graph::new();
+        for (from, to_valves) in self.0.iter() {
+            let from = res.node(from.name(names));
+            for (to, cost) in to_valves.0.iter() {
+                let to = res.node(to.name(names));
+                res.add_edge(from, to, *cost as Weight);
             }
-        }) {
-            CaveMap::remove_useless_valve(&mut res, useless_valve);
         }
-
         res
     }
 
-    fn remove_useless_valve(map: &mut CaveMap, valve: &Valve) {
-        let replacements = map
-            .0
-            .remove(valve)
-            .unwrap()
-            .0
-            .iter()
-            .filter(|(v, _)| v != valve)
-            .cloned()
-            .collect::<Vec<(Valve, usize)>>();
-
-        for (_, vs) in map.0.iter_mut() {
-            if let Some((idx, cost)) =
-                vs.0.iter()
-                    .enumerate()
-                    .find_map(|(i, (v, c))| if v == valve { Some((i, *c)) } else { None })
-            {
-                vs.0.remove(idx);
-                vs.0.extend(replacements.iter().map(|(v, c)| (*v, c + cost)));
-            }
-        }
+    fn from_graph(graph: &Graph, names: &Interner) -> CaveMap {
+        CaveMap(
+            graph
+                .nodes()
+                .map(|id| {
+                    let to_valves = graph
+                        .edges(id)
+                        .iter()
+                        .map(|(to, cost)| (Valve::from(graph.label(*to), names), *cost as usize))
+                        .collect::<Vec<(Valve, usize)>>();
+                    (Valve::from(graph.label(id), names), ToValves(to_valves))
+                })
+                .collect::<HashMap<Valve, ToValves>>(),
+        )
+    }
+
+    fn shrinked_map(&self, rates: &ValveRates, names: &Interner) -> CaveMap {
+        let useless =
+            |label: &str| label != "AA" && rates.0.get(&Valve::from(label, names)) == Some(&0);
+        let graph = self.to_graph(names).contract(|_, label| useless(label));
+        CaveMap::from_graph(&graph, names)
     }
 
     fn removed_loopes(&self) -> CaveMap {
@@ -182,14 +265,52 @@ impl CaveMap {
     }
 }
 
+fn travel_left(actions: &[Action]) -> usize {
+    match actions.last() {
+        Some(Action::GoTo(_, c)) => c - 1,
+        _ => 0,
+    }
+}
+
+// minute, both agents with their remaining travel time, opened valves
+type State = (usize, (Valve, usize), (Valve, usize), u64);
+
 struct CalcContext {
     map: CaveMap,
     rates: ValveRates,
     max_time: usize,
+    start: Valve,
+    dists: Vec<Vec<usize>>,
+    valve_bits: HashMap<Valve, u64>,
+    best_flow: AtomicU64,
+    seen: Mutex<HashMap<State, u64>>,
+}
+
+impl CalcContext {
+    fn dist(&self, from: Valve, to: Valve) -> usize {
+        self.dists[from.0 as usize][to.0 as usize]
+    }
+}
+
+fn valve_dists(map: &CaveMap, names: &Interner) -> Vec<Vec<usize>> {
+    let graph = map.to_graph(names);
+    let mut res = vec![vec![usize::MAX / 2; names.len()]; names.len()];
+    for (from, dists) in graph.floyd_warshall().iter().enumerate() {
+        for (to, dist) in dists.iter().enumerate() {
+            if let Some(dist) = dist {
+                let (from, to) = (
+                    Valve::from(graph.label(from), names),
+                    Valve::from(graph.label(to), names),
+                );
+                res[from.0 as usize][to.0 as usize] = *dist as usize;
+            }
+        }
+    }
+    res
 }
 
 fn calc_optimal_route(ctx: &CalcContext) -> History {
-    calc_optimal_route_impl(ctx, &History::new())
+    calc_optimal_route_impl(ctx, &History::new(ctx.start))
 }
 
 fn get_gotos(pos: &Valve, map: &CaveMap, actions: &Vec<Action>) -> Vec<Action> {
@@ -200,8 +321,8 @@ fn get_gotos(pos: &Valve, map: &CaveMap, actions: &Vec<Action>) -> Vec<Action> {
         .0
         .iter()
         .filter_map(|(v, c)| {
-            if let Some(Action::GoTo(last_v, _)) = last_goto && last_v == v {
-                    None
+            if matches!(last_goto, Some(Action::GoTo(last_v, _)) if last_v == v) {
+                None
             } else {
                 Some(Action::GoTo(*v, *c))
             }
@@ -294,62 +415,166 @@ fn calc_optimal_route_impl(ctx: &CalcContext, last_history: &History) -> History
     hist.current_time += 1;
 
     // when all valves are open or we are out of time
-    if hist.opened_at_min.len() == ctx.map.0.len() || hist.current_time >= ctx.max_time {
+    let closed = ctx
+        .rates
+        .0
+        .iter()
+        .filter(|(v, rate)| **rate > 0 && !hist.opened_at_min.contains_key(v))
+        .count();
+    if closed == 0 || hist.current_time >= ctx.max_time {
         hist.update_current_flow(&ctx.rates, ctx.max_time);
+        ctx.best_flow
+            .fetch_max(hist.current_flow, Ordering::Relaxed);
         return hist;
     }
 
-    let mut res = vec![];
-    for action in get_possible_actions(ctx, &hist) {
-        let mut tmp_hist = hist.clone();
-        tmp_hist.actions_a.push(action.0);
-        tmp_hist.actions_b.push(action.1);
-
-        match action {
-            (Action::Open(v_a), Action::Open(v_b)) => {
-                tmp_hist.opened_at_min.insert(v_a, hist.current_time);
-                tmp_hist.opened_at_min.insert(v_b, hist.current_time);
-            }
-            (Action::Open(v_a), Action::GoTo(v_b, _)) => {
-                tmp_hist.opened_at_min.insert(v_a, hist.current_time);
-                tmp_hist.pos_b = v_b;
-            }
-            (Action::GoTo(v_a, _), Action::Open(v_b)) => {
-                tmp_hist.opened_at_min.insert(v_b, hist.current_time);
-                tmp_hist.pos_a = v_a;
-            }
-            (Action::GoTo(v_a, _), Action::GoTo(v_b, _)) => {
-                tmp_hist.pos_a = v_a;
-                tmp_hist.pos_b = v_b;
-            }
+    // the same state was already reached with at least as much flow
+    let flow = hist.total_flow(&ctx.rates, ctx.max_time);
+    let dominated = match ctx.seen.lock().unwrap().entry(hist.state(ctx)) {
+        Entry::Occupied(seen) if *seen.get() >= flow => true,
+        Entry::Occupied(mut seen) => {
+            seen.insert(flow);
+            false
+        }
+        Entry::Vacant(seen) => {
+            seen.insert(flow);
+            false
         }
+    };
+    if dominated {
+        hist.current_flow = flow;
+        return hist;
+    }
 
-        res.push(calc_optimal_route_impl(ctx, &tmp_hist));
+    // this branch can not beat the best flow found so far
+    if hist.upper_bound(ctx) <= ctx.best_flow.load(Ordering::Relaxed) {
+        hist.update_current_flow(&ctx.rates, ctx.max_time);
+        return hist;
     }
 
-    res.into_iter()
+    #[cfg(not(feature = "parallel"))]
+    let actions = get_possible_actions(ctx, &hist).into_iter();
+    #[cfg(feature = "parallel")]
+    let actions = get_possible_actions(ctx, &hist).into_par_iter();
+
+    actions
+        .map(|action| {
+            let mut tmp_hist = hist.clone();
+            tmp_hist.actions_a.push(action.0);
+            tmp_hist.actions_b.push(action.1);
+
+            match action {
+                (Action::Open(v_a), Action::Open(v_b)) => {
+                    tmp_hist.opened_at_min.insert(v_a, hist.current_time);
+                    tmp_hist.opened_at_min.insert(v_b, hist.current_time);
+                }
+                (Action::Open(v_a), Action::GoTo(v_b, _)) => {
+                    tmp_hist.opened_at_min.insert(v_a, hist.current_time);
+                    tmp_hist.pos_b = v_b;
+                }
+                (Action::GoTo(v_a, _), Action::Open(v_b)) => {
+                    tmp_hist.opened_at_min.insert(v_b, hist.current_time);
+                    tmp_hist.pos_a = v_a;
+                }
+                (Action::GoTo(v_a, _), Action::GoTo(v_b, _)) => {
+                    tmp_hist.pos_a = v_a;
+                    tmp_hist.pos_b = v_b;
+                }
+            }
+
+            calc_optimal_route_impl(ctx, &tmp_hist)
+        })
         .max_by(|ha, hb| ha.current_flow.cmp(&hb.current_flow))
         .unwrap()
 }
 
-pub fn solve_b(loader: &DataLoader) -> Result<String, &str> {
-    let rates = ValveRates::from(loader);
-    let map = CaveMap::from(loader).shrinked_map(&rates).removed_loopes();
+fn best_history(loader: &DataLoader) -> (History, Interner, CalcContext) {
+    let names = valve_names(loader);
+    let rates = ValveRates::from(loader, &names);
+    let map = CaveMap::from(loader, &names)
+        .shrinked_map(&rates, &names)
+        .removed_loopes();
+    let dists = valve_dists(&map, &names);
+    let valve_bits = rates
+        .0
+        .iter()
+        .filter(|(_, rate)| **rate > 0)
+        .enumerate()
+        .map(|(i, (v, _))| (*v, 1 << i))
+        .collect();
 
     let ctx = CalcContext {
         map,
         rates,
-        max_time: 13,
+        max_time: 26,
+        start: Valve::from("AA", &names),
+        dists,
+        valve_bits,
+        best_flow: AtomicU64::new(0),
+        seen: Mutex::new(HashMap::new()),
     };
-    println!("map={:?}\n", ctx.map);
-    println!("rates={:?}\n", ctx.rates);
+    debug!("map={:?}", ctx.map);
+    debug!("rates={:?}", ctx.rates);
     let rates_count = ctx.rates.0.iter().filter(|(v, c)| c > &&0).count();
-    let rates_x = ctx.rates.0.iter().filter_map(|(v, c)| if c > &&0 {Some(c.clone())} else {None}).collect::<Vec<u64>>();
-    println!("rates_count={}", rates_count);
-    println!("rates_x={:?}", rates_x);
+    let rates_x = ctx
+        .rates
+        .0
+        .iter()
+        .filter_map(|(v, c)| if c > &&0 { Some(c.clone()) } else { None })
+        .collect::<Vec<u64>>();
+    debug!("rates_count={}", rates_count);
+    debug!("rates_x={:?}", rates_x);
 
     let history = calc_optimal_route(&ctx);
-    println!("History={:?}\n", history);
+    debug!("history={:?}", history);
+
+    (history, names, ctx)
+}
+
+pub fn solve_b(loader: &DataLoader) -> Result<String, &str> {
+    let (history, names, ctx) = best_history(loader);
+    for line in history.plan(&names, &ctx.rates, ctx.max_time) {
+        info!("{}", line);
+    }
 
     Ok(history.current_flow.to_string())
 }
+
+pub fn plan_b(loader: &DataLoader) -> Result<String, &str> {
+    let (history, names, ctx) = best_history(loader);
+    Ok(history.plan(&names, &ctx.rates, ctx.max_time).join("\n"))
+}
+
+#[cfg(test)]
+mod test_main {
+    use super::*;
+
+    #[test]
+    fn plan_test() {
+        let loader = DataLoader::from_file("data/test_input.txt");
+        let names = valve_names(&loader);
+        let rates = ValveRates::from(&loader, &names);
+        let valve = |name| Valve::from(name, &names);
+        let mut hist = History::new(valve("AA"));
+
+        hist.actions_a = vec![Action::GoTo(valve("II"), 1), Action::GoTo(valve("JJ"), 1)];
+        hist.actions_a.push(Action::Open(valve("JJ")));
+        hist.actions_b = vec![Action::GoTo(valve("DD"), 1), Action::Open(valve("DD"))];
+        hist.opened_at_min.insert(valve("DD"), 2);
+        hist.opened_at_min.insert(valve("JJ"), 3);
+
+        assert_eq!(
+            hist.plan(&names, &rates, 4),
+            vec![
+                "minute 1: you move to II, elephant move to DD, flow 0",
+                "minute 2: you move to JJ, elephant open DD, flow 0",
+                "minute 3: you open JJ, elephant wait, flow 20",
+                "minute 4: you wait, elephant wait, flow 41",
+            ]
+        );
+
+        let plan = plan_b(&loader).unwrap();
+        assert_eq!(plan.lines().count(), 26);
+        assert!(plan.ends_with("flow 81"));
+    }
+}
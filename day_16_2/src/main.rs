@@ -2,11 +2,14 @@
 #![allow(unused_variables, dead_code)]
 
 use aoc_helpers::data_loader::DataLoader;
+use aoc_helpers::logging;
 
 mod solver;
 use crate::solver::{solve_a, solve_b};
 
 fn main() {
+    logging::init();
+
     let a_input = DataLoader::from_file("data/puzzle_input.txt");
     println!("a: {}", solve_a(&a_input).unwrap());
     println!("b: {}", solve_b(&a_input).unwrap());
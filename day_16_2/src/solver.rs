@@ -1,45 +1,41 @@
 use aoc_helpers::data_loader::DataLoader;
-//use itertools::Itertools;
+use aoc_helpers::graph::{Graph, Weight};
+use aoc_helpers::interner::{Id, Interner};
+use itertools::Itertools;
 use std::collections::HashMap;
-use comparator::collections::BinaryHeap;
-use std::fmt;
+use tracing::debug;
 
-#[derive(Clone, Copy, Hash, PartialEq, Eq)]
-struct Valve(u8, u8);
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+struct Valve(Id);
 
 impl Valve {
-    fn from(raw_valve: &str) -> Valve {
-        Valve(
-            raw_valve.chars().nth(0).unwrap() as u8,
-            raw_valve.chars().nth(1).unwrap() as u8,
-        )
+    fn from(raw_valve: &str, names: &Interner) -> Valve {
+        Valve(names.get(raw_valve).expect("unknown valve"))
     }
 
-    fn from_line(line: &str) -> Valve {
-        line.split(" ")
-            .enumerate()
-            .filter_map(|(i, s)| match i {
-                1 => Some(Valve::from(s)),
-                _ => None,
-            })
-            .nth(0)
-            .unwrap()
+    fn from_line(line: &str, names: &Interner) -> Valve {
+        Valve::from(line.split(' ').nth(1).unwrap(), names)
     }
-}
 
-impl fmt::Debug for Valve {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}{}", self.0 as char, self.1 as char)
+    fn name(self, names: &Interner) -> &str {
+        names.resolve(self.0)
     }
 }
 
+fn valve_names(loader: &DataLoader) -> Interner {
+    loader
+        .iter()
+        .map(|line| line.split(' ').nth(1).unwrap())
+        .collect::<Interner>()
+}
+
 type Cost = usize;
 
 #[derive(Debug, Clone)]
 struct ToValves(Vec<(Valve, Cost)>);
 
 impl ToValves {
-    fn from_line(line: &String) -> ToValves {
+    fn from_line(line: &str, names: &Interner) -> ToValves {
         ToValves(
             line.split_once("; ")
                 .unwrap()
@@ -47,7 +43,7 @@ impl ToValves {
                 .replace("s", "")
                 .replace("tunnel lead to valve ", "")
                 .split(',')
-                .map(|s| (Valve::from(s.trim()), 1))
+                .map(|s| (Valve::from(s.trim(), names), 1))
                 .collect::<Vec<(Valve, Cost)>>(),
         )
     }
@@ -57,7 +53,7 @@ impl ToValves {
 struct ValveRates(HashMap<Valve, u64>);
 
 impl ValveRates {
-    fn from(loader: &DataLoader) -> ValveRates {
+    fn from(loader: &DataLoader, names: &Interner) -> ValveRates {
         ValveRates(
             loader
                 .iter()
@@ -74,7 +70,7 @@ impl ValveRates {
                         })
                         .collect::<Vec<&str>>()
                 })
-                .map(|v| (Valve::from(v[0]), v[1].parse::<u64>().unwrap()))
+                .map(|v| (Valve::from(v[0], names), v[1].parse::<u64>().unwrap()))
                 .collect::<HashMap<Valve, u64>>(),
         )
     }
@@ -84,111 +80,157 @@ impl ValveRates {
 struct CaveMap(HashMap<Valve, ToValves>);
 
 impl CaveMap {
-    fn from(loader: &DataLoader) -> CaveMap {
+    fn from(loader: &DataLoader, names: &Interner) -> CaveMap {
         CaveMap(
             loader
                 .iter()
-                .map(|line| (Valve::from_line(line), ToValves::from_line(line)))
+                .map(|line| {
+                    (
+                        Valve::from_line(line, names),
+                        ToValves::from_line(line, names),
+                    )
+                })
                 .collect::<HashMap<Valve, ToValves>>(),
         )
     }
 
-    fn shrinked_map(&self, rates: &ValveRates) -> CaveMap {
-        let mut res = self.clone();
-        for useless_valve in rates.0.iter().filter_map(|(v, r)| {
-            if r == &0 && *v != Valve::from("AA") {
-                Some(v)
-            } else {
-                None
+    fn to_graph(&self, names: &Interner) -> Graph {
+        let mut res = Graph::new();
+        for (from, to_valves) in self.0.iter() {
+            let from = res.node(from.name(names));
+            for (to, cost) in to_valves.0.iter() {
+                let to = res.node(to.name(names));
+                res.add_edge(from, to, *cost as Weight);
             }
-        }) {
-            CaveMap::remove_useless_valve(&mut res, useless_valve);
         }
-
         res
     }
 
-    fn remove_useless_valve(map: &mut CaveMap, valve: &Valve) {
-        let replacements = map
-            .0
-            .remove(valve)
-            .unwrap()
-            .0
-            .iter()
-            .filter(|(v, _)| v != valve)
-            .cloned()
-            .collect::<Vec<(Valve, usize)>>();
-
-        for (_, vs) in map.0.iter_mut() {
-            if let Some((idx, cost)) =
-                vs.0.iter()
-                    .enumerate()
-                    .find_map(|(i, (v, c))| if v == valve { Some((i, *c)) } else { None })
-            {
-                vs.0.remove(idx);
-                vs.0.extend(replacements.iter().map(|(v, c)| (*v, c + cost)));
-            }
-        }
-    }
-
-    fn removed_loopes(&self) -> CaveMap {
-        let mut res = self.clone();
-        for (v, vs) in res.0.iter_mut() {
-            vs.0.retain(|(w, _)| v != w);
-        }
-        res
+    fn shrinked_graph(&self, rates: &ValveRates, names: &Interner) -> Graph {
+        let useless =
+            |label: &str| label != "AA" && rates.0.get(&Valve::from(label, names)) == Some(&0);
+        self.to_graph(names).contract(|_, label| useless(label))
     }
 }
 
-//fn calc_shortest_path(start: Pos, mat: &Matrix<char>) -> Matrix<u64> {
-    //let mut res: Matrix<u64> = Matrix::from_iter(mat.rows(), mat.cols(), iter::repeat(u64::MAX));
-    //let mut jobs = BinaryHeap::with_comparator(comparing(|job: &Job| u64::MAX - job.steps()));
+struct Tunnels {
+    valves: Vec<Valve>,
+    rates: Vec<u64>,
+    costs: Vec<Vec<Cost>>,
+    start: usize,
+}
 
-    //res.set(start.row(), start.col(), 0);
-    //jobs.push(Job(0, start));
+impl Tunnels {
+    fn from(loader: &DataLoader) -> Tunnels {
+        let names = valve_names(loader);
+        let rates = ValveRates::from(loader, &names);
+        let graph = CaveMap::from(loader, &names).shrinked_graph(&rates, &names);
+        debug!("graph={:?}", graph);
+        debug!("rates={:?}", rates);
 
-    //while let Some(job) = jobs.pop() {
-        //let next_step_count = job.steps() + 1;
+        let paths = graph.floyd_warshall();
 
-        //let next_poses = get_sorounding_poses(job.pos())
-            //.into_iter()
-            //.filter(|p| p.in_boundary_of(mat))
-            //.filter(|p| res.get(p.row(), p.col()).unwrap() > &next_step_count)
-            //.filter(|p| {
-                //(mat.get(p.row(), p.col()).unwrap().clone() as u8)
-                    //<= (mat.get(job.pos().row(), job.pos().col()).unwrap().clone() as u8) + 1
-            //})
-            //.collect::<Vec<Pos>>();
+        let mut ids = graph
+            .nodes()
+            .filter(|id| rates.0.get(&Valve::from(graph.label(*id), &names)).unwrap() > &0)
+            .sorted_by_key(|id| graph.label(*id))
+            .collect_vec();
+        ids.push(graph.id("AA").unwrap());
 
-        //for next_pos in next_poses {
-            //res.set(next_pos.row(), next_pos.col(), next_step_count);
-            //jobs.push(Job(next_step_count, next_pos));
-        //}
-    //}
+        let valves = ids
+            .iter()
+            .map(|id| Valve::from(graph.label(*id), &names))
+            .collect_vec();
+        let costs = ids
+            .iter()
+            .map(|from| {
+                ids.iter()
+                    .map(|to| paths[*from][*to].map_or(Cost::MAX, |cost| cost as Cost))
+                    .collect_vec()
+            })
+            .collect_vec();
 
-    //res
-//}
+        Tunnels {
+            rates: valves
+                .iter()
+                .map(|v| *rates.0.get(v).unwrap())
+                .collect_vec(),
+            start: valves.len() - 1,
+            valves,
+            costs,
+        }
+    }
 
-fn get_shortest_pahts(from: &Valve, map: &CaveMap) -> HashMap<(Valve, Valve), Cost>{
-    let mut res: HashMap<(Valve, Valve), Cost> = HashMap::new();
-    //let mut jobs = BinaryHeap::with_comparator(comparing(|job: &Job| u64::MAX - job.steps()));
+    fn useful_count(&self) -> usize {
+        self.start
+    }
 
-    while true {
+    // best released pressure for every set of opened valves, DP over (position, opened, time)
+    fn best_per_opened_set(&self, max_time: usize) -> HashMap<u32, u64> {
+        let mut states: Vec<HashMap<(usize, u32), u64>> = vec![HashMap::new(); max_time + 1];
+        states[0].insert((self.start, 0), 0);
+
+        let mut res: HashMap<u32, u64> = HashMap::new();
+        for time in 0..=max_time {
+            for ((pos, opened), pressure) in std::mem::take(&mut states[time]) {
+                let best = res.entry(opened).or_insert(0);
+                *best = (*best).max(pressure);
+
+                for next in (0..self.useful_count()).filter(|next| opened & 1 << next == 0) {
+                    let cost = self.costs[pos][next];
+                    if cost == Cost::MAX || time + cost + 1 >= max_time {
+                        continue;
+                    }
+
+                    let open_at = time + cost + 1;
+                    let released = pressure + self.rates[next] * (max_time - open_at) as u64;
+                    let entry = states[open_at]
+                        .entry((next, opened | 1 << next))
+                        .or_insert(0);
+                    *entry = (*entry).max(released);
+                }
+            }
+        }
 
+        res
     }
-    todo!();
 }
 
 pub fn solve_a(loader: &DataLoader) -> Result<String, &str> {
-    let rates = ValveRates::from(loader);
-    let map = CaveMap::from(loader).shrinked_map(&rates).removed_loopes();
+    let tunnels = Tunnels::from(loader);
+    if tunnels.useful_count() > 32 {
+        return Err("Too many valves");
+    }
 
-    println!("map={:?}\n", map);
-    println!("rates={:?}\n", rates);
+    let best = tunnels.best_per_opened_set(30);
 
-    Ok(String::new())
+    Ok(best.values().max().unwrap().to_string())
 }
 
 pub fn solve_b(loader: &DataLoader) -> Result<String, &str> {
-    Ok(String::new())
+    let tunnels = Tunnels::from(loader);
+    if tunnels.useful_count() > 32 {
+        return Err("Too many valves");
+    }
+
+    let best = tunnels
+        .best_per_opened_set(26)
+        .into_iter()
+        .sorted_by_key(|(_, pressure)| u64::MAX - pressure)
+        .collect_vec();
+    debug!("opened sets={}", best.len());
+
+    let mut res = 0;
+    for (i, (own, own_pressure)) in best.iter().enumerate() {
+        if own_pressure * 2 < res {
+            break;
+        }
+        if let Some((_, elephant_pressure)) =
+            best[i..].iter().find(|(elephant, _)| own & elephant == 0)
+        {
+            res = res.max(own_pressure + elephant_pressure);
+        }
+    }
+
+    Ok(res.to_string())
 }
@@ -1,11 +1,11 @@
+use aoc_helpers::animation::Animation;
+use aoc_helpers::cycle::{find_repeat_by, Cycle};
 use aoc_helpers::data_loader::DataLoader;
-use aoc_helpers::matrix_helper::print_matrix;
+use aoc_helpers::render::{Layer, Orientation, Render, Renderer, Viewport};
 use itertools::Itertools;
-use simple_matrix::Matrix;
-use std::cmp::max;
-use std::collections::{HashSet, VecDeque};
-use std::iter;
+use std::collections::VecDeque;
 use std::ops::Add;
+use tracing::{info, trace};
 
 #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
 struct Pos(i64, i64);
@@ -40,63 +40,84 @@ impl Add for Pos {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum Shape {
-    Bar(Pos),
-    Cross(Pos),
-    Angle(Pos),
-    Stick(Pos),
-    Block(Pos),
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Shape {
+    rows: Vec<u8>,
+    width: usize,
 }
 
 impl Shape {
-    fn expansion(&self) -> Vec<Pos> {
-        self.rel_expansion()
+    fn from_rows(rows: &[u8]) -> Shape {
+        let width = rows
             .iter()
-            .map(|p| *p + self.pos())
-            .collect_vec()
-    }
-
-    fn rel_expansion(&self) -> Vec<Pos> {
-        match self {
-            Shape::Bar(_) => vec![Pos(0, 0), Pos(1, 0), Pos(2, 0), Pos(3, 0)],
-            Shape::Cross(_) => vec![Pos(1, 0), Pos(0, -1), Pos(1, -1), Pos(2, -1), Pos(1, -2)],
-            Shape::Angle(_) => vec![Pos(2, 0), Pos(2, -1), Pos(0, -2), Pos(1, -2), Pos(2, -2)],
-            Shape::Stick(_) => vec![Pos(0, 0), Pos(0, -1), Pos(0, -2), Pos(0, -3)],
-            Shape::Block(_) => vec![Pos(0, 0), Pos(1, 0), Pos(0, -1), Pos(1, -1)],
+            .map(|row| 8 - row.leading_zeros() as usize)
+            .max()
+            .unwrap_or(0);
+        Shape {
+            rows: rows.to_vec(),
+            width,
         }
     }
 
     fn height(&self) -> usize {
-        let (min, max) = self
-            .rel_expansion()
-            .into_iter()
-            .map(|p| p.y())
-            .minmax()
-            .into_option()
-            .unwrap();
-        1 + (max - min).abs() as usize
+        self.rows.len()
     }
 
-    fn pos(&self) -> Pos {
-        match self {
-            Shape::Bar(pos) => *pos,
-            Shape::Cross(pos) => *pos,
-            Shape::Angle(pos) => *pos,
-            Shape::Stick(pos) => *pos,
-            Shape::Block(pos) => *pos,
-        }
+    fn expansion(&self, pos: Pos) -> Vec<Pos> {
+        self.rows
+            .iter()
+            .enumerate()
+            .flat_map(|(dy, row)| {
+                (0..self.width)
+                    .filter(move |dx| row & (1 << dx) != 0)
+                    .map(move |dx| Pos(pos.x() + dx as i64, pos.y() + dy as i64))
+            })
+            .collect_vec()
     }
+}
 
-    fn update_pos(self, pos: Pos) -> Shape {
-        match self {
-            Shape::Bar(_) => Shape::Bar(pos),
-            Shape::Cross(_) => Shape::Cross(pos),
-            Shape::Angle(_) => Shape::Angle(pos),
-            Shape::Stick(_) => Shape::Stick(pos),
-            Shape::Block(_) => Shape::Block(pos),
+fn default_shapes() -> Vec<Shape> {
+    vec![
+        Shape::from_rows(&[0b1111]),
+        Shape::from_rows(&[0b010, 0b111, 0b010]),
+        Shape::from_rows(&[0b111, 0b100, 0b100]),
+        Shape::from_rows(&[0b1, 0b1, 0b1, 0b1]),
+        Shape::from_rows(&[0b11, 0b11]),
+    ]
+}
+
+fn load_shapes(loader: &DataLoader) -> Result<Vec<Shape>, &'static str> {
+    let mut res = vec![];
+    let mut rows: Vec<u8> = vec![];
+
+    for line in loader.iter().map(|l| l.trim_end()).chain([""]) {
+        if line.is_empty() {
+            if !rows.is_empty() {
+                rows.reverse();
+                res.push(Shape::from_rows(&rows));
+                rows.clear();
+            }
+            continue;
+        }
+
+        if line.len() > 8 {
+            return Err("Shape wider than 8 cells");
         }
+        let row = line
+            .chars()
+            .enumerate()
+            .try_fold(0_u8, |acc, (x, c)| match c {
+                '#' => Ok(acc | 1 << x),
+                '.' => Ok(acc),
+                _ => Err("Invalid shape cell"),
+            })?;
+        rows.push(row);
+    }
+
+    if res.is_empty() {
+        return Err("No shapes found");
     }
+    Ok(res)
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -132,168 +153,162 @@ fn get_gas_jets(loader: &DataLoader) -> GasJets {
         .collect::<VecDeque<Direct>>()
 }
 
+struct JetStream<'a> {
+    jets: &'a GasJets,
+    idx: usize,
+}
+
+impl<'a> JetStream<'a> {
+    fn new(jets: &'a GasJets) -> JetStream<'a> {
+        JetStream { jets, idx: 0 }
+    }
+}
+
+impl<'a> Iterator for JetStream<'a> {
+    type Item = &'a Direct;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let res = self.jets.get(self.idx);
+        self.idx = (self.idx + 1) % self.jets.len();
+        res
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 struct Board {
     width: usize,
-    taken_spaces: HashSet<Pos>,
+    spawn_left: usize,
+    spawn_gap: usize,
+    rows: Vec<u8>,
 }
 
-impl Board {
-    pub(self) fn set_floor(&mut self) {
-        for x in 0..self.width {
-            self.taken_spaces.insert(Pos(x as i64, -1));
+impl Default for Board {
+    fn default() -> Self {
+        Board {
+            width: 7,
+            spawn_left: 2,
+            spawn_gap: 3,
+            rows: vec![],
         }
     }
+}
 
-    fn new() -> Self {
-        let mut board = Board {
-            width: 7,
-            taken_spaces: HashSet::new(),
-        };
-
-        board.set_floor();
-        board
+impl Board {
+    fn new(width: usize, spawn_left: usize, spawn_gap: usize) -> Result<Self, &'static str> {
+        if width > 8 {
+            return Err("chamber wider than 8 columns");
+        }
+        Ok(Board {
+            width,
+            spawn_left,
+            spawn_gap,
+            rows: vec![],
+        })
     }
 
-    fn drop_shape<'a, I>(&mut self, shape: Shape, gas_jets: &mut I)
+    fn drop_shape<'a, I>(&mut self, shape: &Shape, gas_jets: &mut I)
     where
         I: Iterator<Item = &'a Direct>,
     {
-        let start_pos = self.get_shape_start_pos(shape);
-        self.drop_shape_impl(shape.update_pos(start_pos), gas_jets);
-    }
+        let mut pos = self.get_shape_start_pos();
 
-    pub(self) fn drop_shape_impl<'a, I>(&mut self, shape: Shape, gas_jets: &mut I)
-    where
-        I: Iterator<Item = &'a Direct>,
-    {
-        let to_direct = *gas_jets.next().unwrap();
-        let (_, shape_1) = self.try_move_shape(shape, to_direct);
-        let (moved_down, shape_2) = self.try_move_shape(shape_1, Direct::Down);
+        loop {
+            let to_direct = *gas_jets.next().unwrap();
+            if self.fits(shape, pos + to_direct.pos()) {
+                pos = pos + to_direct.pos();
+            }
 
-        if moved_down {
-            self.drop_shape_impl(shape_2, gas_jets);
-        } else {
-            for pos in shape_2.expansion() {
-                if !self.taken_spaces.insert(pos) {
-                    panic!("space allready occupied");
-                }
+            if self.fits(shape, pos + Direct::Down.pos()) {
+                pos = pos + Direct::Down.pos();
+            } else {
+                break;
             }
         }
+
+        self.place(shape, pos);
     }
 
-    pub(self) fn try_move_shape(&mut self, shape: Shape, direct: Direct) -> (bool, Shape) {
-        let next_shape = shape.update_pos(shape.pos() + direct.pos());
-        match direct {
-            Direct::Left => {
-                if next_shape
-                    .expansion()
-                    .into_iter()
-                    .all(|p| p.x() >= 0 && !self.taken_spaces.contains(&p))
-                {
-                    (true, next_shape)
-                } else {
-                    (false, shape)
-                }
-            }
-            Direct::Right => {
-                if next_shape
-                    .expansion()
-                    .into_iter()
-                    .all(|p| p.x() < self.width as i64 && !self.taken_spaces.contains(&p))
-                {
-                    (true, next_shape)
-                } else {
-                    (false, shape)
-                }
+    fn fits(&self, shape: &Shape, pos: Pos) -> bool {
+        if pos.x() < 0 || pos.y() < 0 || pos.x() as usize + shape.width > self.width {
+            return false;
+        }
+
+        shape.rows.iter().enumerate().all(|(dy, row)| {
+            let board_row = self.rows.get(pos.y() as usize + dy).copied().unwrap_or(0);
+            board_row & (row << pos.x()) == 0
+        })
+    }
+
+    fn place(&mut self, shape: &Shape, pos: Pos) {
+        for (dy, row) in shape.rows.iter().enumerate() {
+            let y = pos.y() as usize + dy;
+            if y >= self.rows.len() {
+                self.rows.resize(y + 1, 0);
             }
-            Direct::Down => {
-                if next_shape
-                    .expansion()
-                    .into_iter()
-                    .all(|p| !self.taken_spaces.contains(&p))
-                {
-                    (true, next_shape)
-                } else {
-                    (false, shape)
-                }
+            if self.rows[y] & (row << pos.x()) != 0 {
+                panic!("space allready occupied");
             }
+            self.rows[y] |= row << pos.x();
         }
     }
 
-    pub(self) fn get_shape_start_pos(&self, shape: Shape) -> Pos {
-        Pos(2, shape.height() as i64 + self.board_height() as i64 + 2)
+    pub(self) fn get_shape_start_pos(&self) -> Pos {
+        Pos(
+            self.spawn_left as i64,
+            (self.board_height() + self.spawn_gap) as i64,
+        )
     }
 
     fn board_height(&self) -> usize {
-        if let Some(res) = self
-            .taken_spaces
-            .iter()
-            .filter(|p| p.y() >= 0)
-            .max_by(|l, r| l.y().cmp(&r.y()))
-        {
-            res.y() as usize + 1
-        } else {
-            0
-        }
+        self.rows.len()
     }
 
-    fn print(&self, shape: Option<Shape>) {
-        let height = if let Some(s) = shape {
-            max(
-                self.board_height(),
-                1 + s.expansion().into_iter().map(|p| p.y()).max().unwrap() as usize,
-            )
-        } else {
-            self.board_height()
-        };
-
-        if height == 0 {
-            println!("Boad is empty");
-            return;
+    fn trace(&self, shape: Option<(&Shape, Pos)>) {
+        let mut renderer = Renderer::new().orientation(Orientation::YUp);
+        if let Some((s, pos)) = shape {
+            renderer = renderer.layer(Layer::from_iter(
+                s.expansion(pos).into_iter().map(|p| ((p.x(), p.y()), '@')),
+            ));
         }
+        trace!("\n{}", renderer.render(self));
+    }
 
-        let mut mat = Matrix::from_iter(height, self.width, iter::repeat('.'));
-
-        let shape_vec = if let Some(s) = shape {
-            s.expansion()
-        } else {
-            vec![]
-        };
+    fn surface_profile(&self) -> Vec<usize> {
+        (0..self.width)
+            .map(|x| {
+                self.rows
+                    .iter()
+                    .rev()
+                    .position(|row| row & (1 << x) != 0)
+                    .unwrap_or(self.rows.len() + 1)
+            })
+            .collect_vec()
+    }
+}
 
-        for pos in self.taken_spaces.iter().chain(shape_vec.iter()) {
-            if pos.y() < 0 {
-                continue;
-            }
-            mat.set(height - 1 - pos.y() as usize, pos.x() as usize, '#');
+impl Render for Board {
+    fn viewport(&self) -> Option<Viewport> {
+        match self.board_height() {
+            0 => None,
+            height => Some(Viewport::new(
+                0,
+                0,
+                self.width as i64 - 1,
+                height as i64 - 1,
+            )),
         }
-
-        print_matrix(&mat);
-        println!("");
     }
 
-    fn trimmed(&self, max_height: usize) -> Board {
-        let height = self.board_height();
-        if height <= max_height {
-            return self.clone();
+    fn cell(&self, x: i64, y: i64) -> char {
+        if self
+            .rows
+            .get(y as usize)
+            .is_some_and(|row| row & (1 << x) != 0)
+        {
+            '#'
+        } else {
+            '.'
         }
-
-        let min_height = height as i64 - max_height as i64;
-
-        let mut res = self.clone();
-        res.taken_spaces = res
-            .taken_spaces
-            .into_iter()
-            .filter_map(|p| {
-                if p.y() >= min_height {
-                    Some(p + Pos(0, -min_height))
-                } else {
-                    None
-                }
-            })
-            .collect::<HashSet<Pos>>();
-
-        res
     }
 }
 
@@ -306,11 +321,27 @@ fn stack_it<'a, 'b, JET, SHAPE>(
 where
     JET: Iterator<Item = &'a Direct>,
     SHAPE: Iterator<Item = &'b Shape>,
+{
+    stack_it_with(count, board, jets, shapes, |_| ())
+}
+
+fn stack_it_with<'a, 'b, JET, SHAPE, F>(
+    count: usize,
+    board: Board,
+    jets: &mut JET,
+    shapes: &mut SHAPE,
+    mut on_step: F,
+) -> Board
+where
+    JET: Iterator<Item = &'a Direct>,
+    SHAPE: Iterator<Item = &'b Shape>,
+    F: FnMut(&Board),
 {
     let mut res = board.clone();
 
     for _ in 0..count {
-        res.drop_shape(*shapes.next().unwrap(), jets);
+        res.drop_shape(shapes.next().unwrap(), jets);
+        on_step(&res);
     }
 
     res
@@ -318,15 +349,9 @@ where
 
 pub fn solve_a(loader: &DataLoader) -> Result<String, &str> {
     let jets = get_gas_jets(loader);
-    let board = Board::new();
-
-    let shape_order = [
-        Shape::Bar(Pos::new()),
-        Shape::Cross(Pos::new()),
-        Shape::Angle(Pos::new()),
-        Shape::Stick(Pos::new()),
-        Shape::Block(Pos::new()),
-    ];
+    let board = Board::default();
+
+    let shape_order = default_shapes();
     let mut shapes_iter = shape_order.iter().cycle();
     let mut jets_iter = jets.iter().cycle();
 
@@ -335,52 +360,189 @@ pub fn solve_a(loader: &DataLoader) -> Result<String, &str> {
     Ok(board.board_height().to_string())
 }
 
-pub fn solve_b(loader: &DataLoader) -> Result<String, &str> {
+pub fn tower_height(
+    loader: &DataLoader,
+    shapes: &DataLoader,
+    width: usize,
+    spawn_left: usize,
+    spawn_gap: usize,
+    rocks: usize,
+) -> Result<usize, &'static str> {
+    let board = Board::new(width, spawn_left, spawn_gap)?;
     let jets = get_gas_jets(loader);
 
-    let shapes_order = [
-        Shape::Bar(Pos::new()),
-        Shape::Cross(Pos::new()),
-        Shape::Angle(Pos::new()),
-        Shape::Stick(Pos::new()),
-        Shape::Block(Pos::new()),
-    ];
-    let mut shapes_iter = shapes_order.iter().cycle();
+    let shape_order = load_shapes(shapes)?;
+    if shape_order
+        .iter()
+        .any(|shape| spawn_left + shape.width > width)
+    {
+        return Err("Shape does not fit into chamber");
+    }
+    let mut shapes_iter = shape_order.iter().cycle();
+    let mut jets_iter = jets.iter().cycle();
+
+    let board = stack_it(rocks, board, &mut jets_iter, &mut shapes_iter);
+
+    Ok(board.board_height())
+}
+
+pub fn render_a(loader: &DataLoader, rocks: usize) -> Result<String, &str> {
+    let jets = get_gas_jets(loader);
+
+    let shape_order = default_shapes();
+    let mut shapes_iter = shape_order.iter().cycle();
     let mut jets_iter = jets.iter().cycle();
 
-    let cycles = jets.len() * shapes_order.len();
+    let board = stack_it(rocks, Board::default(), &mut jets_iter, &mut shapes_iter);
+
+    Ok(Renderer::new().orientation(Orientation::YUp).render(&board))
+}
+
+pub fn animate_a(loader: &DataLoader, rocks: usize) -> Result<Animation, &str> {
+    let jets = get_gas_jets(loader);
+
+    let shape_order = default_shapes();
+    let mut shapes_iter = shape_order.iter().cycle();
+    let mut jets_iter = jets.iter().cycle();
 
-    let mut boards: Vec<Board> = vec![];
-    boards.push(stack_it(
-        cycles,
-        Board::new(),
+    let mut animation = Animation::new(Renderer::new().orientation(Orientation::YUp));
+    stack_it_with(
+        rocks,
+        Board::default(),
         &mut jets_iter,
         &mut shapes_iter,
-    ));
+        |board| animation.push(board),
+    );
 
-    let starting_boards;
-    let repeating_boards;
+    Ok(animation)
+}
 
-    loop {
-        let last_board = boards.last().unwrap();
-        println!("boards.len={}", boards.len());
-        let board = stack_it(cycles, last_board.clone(), &mut jets_iter, &mut shapes_iter)
-            .trimmed(last_board.board_height());
+pub fn solve_b(loader: &DataLoader) -> Result<String, &str> {
+    let (cycle, heights) = find_rock_cycle(loader)?;
+    info!("cycle start={} length={}", cycle.start, cycle.length);
 
-        if let Some(idx) = boards.iter().position(|b| b == &board) {
-            starting_boards = boards.iter().take(idx).cloned().collect_vec();
-            repeating_boards = boards.iter().skip(idx).cloned().collect_vec();
-            break;
-        } else {
-            boards.push(board);
+    Ok(cycle.extrapolate(&heights, 1000000000000).to_string())
+}
+
+pub fn find_rock_cycle(loader: &DataLoader) -> Result<(Cycle, Vec<i64>), &str> {
+    let jets = get_gas_jets(loader);
+
+    let shapes_order = default_shapes();
+    let mut shapes_iter = shapes_order.iter().cycle();
+    let mut jets_iter = JetStream::new(&jets);
+    let mut board = Board::default();
+
+    let states = (0..).map(|rock| {
+        if rock > 0 {
+            board.drop_shape(shapes_iter.next().unwrap(), &mut jets_iter);
         }
+        trace!("rock={} jet={}", rock, jets_iter.idx);
+        let key = (
+            rock % shapes_order.len(),
+            jets_iter.idx,
+            board.surface_profile(),
+        );
+        (key, board.board_height() as i64)
+    });
+
+    let (cycle, history) =
+        find_repeat_by(states, |(key, _)| key.clone()).ok_or("No cycle found")?;
+    let heights = history.into_iter().map(|(_, height)| height).collect_vec();
+
+    Ok((cycle, heights))
+}
+
+#[cfg(test)]
+mod test_main {
+    use super::*;
+
+    fn fuzz_jets(seed: u64, len: usize) -> DataLoader {
+        let mut state = seed;
+        let jets = (0..len)
+            .map(|_| {
+                state = state
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                if state >> 63 == 0 {
+                    '<'
+                } else {
+                    '>'
+                }
+            })
+            .collect::<String>();
+        DataLoader::from_data(&vec![jets])
     }
 
-    println!("boards.len={}", boards.len());
-    println!("starting boards.len={}", starting_boards.len());
-    println!("repeating boards.len={}", repeating_boards.len());
+    #[test]
+    fn load_shapes_test() {
+        assert_eq!(
+            load_shapes(&DataLoader::from_file("data/shapes.txt")).unwrap(),
+            default_shapes()
+        );
+        assert!(load_shapes(&DataLoader::from_data(&vec!["#x".to_string()])).is_err());
+        assert!(load_shapes(&DataLoader::from_data(&vec![])).is_err());
+    }
 
-    //board_1.trimmed(board_0.board_height()).print(None);
+    #[test]
+    fn custom_chamber_test() {
+        let loader = DataLoader::from_file("data/test_input.txt");
+        let shapes = DataLoader::from_file("data/shapes.txt");
+        assert_eq!(tower_height(&loader, &shapes, 7, 2, 3, 2022), Ok(3068));
+        assert_eq!(
+            tower_height(&loader, &shapes, 5, 2, 3, 1),
+            Err("Shape does not fit into chamber")
+        );
+        assert_eq!(
+            tower_height(&loader, &shapes, 7, 4, 3, 1),
+            Err("Shape does not fit into chamber")
+        );
+        assert_eq!(
+            tower_height(&loader, &shapes, 9, 2, 3, 1),
+            Err("chamber wider than 8 columns")
+        );
+
+        let dots = DataLoader::from_data(&vec!["#".to_string()]);
+        let right = DataLoader::from_data(&vec![">".to_string()]);
+        assert_eq!(tower_height(&right, &dots, 4, 2, 0, 10), Ok(10));
+        assert_eq!(tower_height(&right, &dots, 4, 2, 5, 10), Ok(10));
+        assert_eq!(tower_height(&right, &dots, 4, 0, 0, 10), Ok(10));
+    }
 
-    Ok(String::new())
+    #[test]
+    fn fuzz_jets_test() {
+        let shapes = default_shapes();
+        let cells_per_cycle: u32 = shapes
+            .iter()
+            .flat_map(|shape| shape.rows.iter())
+            .map(|row| row.count_ones())
+            .sum();
+
+        for seed in 0..20 {
+            let jets = get_gas_jets(&fuzz_jets(seed, 1 + seed as usize * 7));
+            let mut jets_iter = jets.iter().cycle();
+            let mut board = Board::default();
+            let mut last_height = 0;
+
+            for (rock, shape) in shapes.iter().cycle().take(500).enumerate() {
+                board.drop_shape(shape, &mut jets_iter);
+
+                let height = board.board_height();
+                assert!(height >= last_height);
+                assert!(height <= last_height + shape.height());
+                last_height = height;
+
+                assert!(board
+                    .rows
+                    .iter()
+                    .all(|row| *row != 0 && row >> board.width == 0));
+                if (rock + 1) % shapes.len() == 0 {
+                    let cells: u32 = board.rows.iter().map(|row| row.count_ones()).sum();
+                    assert_eq!(
+                        cells,
+                        cells_per_cycle * (rock as u32 + 1) / shapes.len() as u32
+                    );
+                }
+            }
+        }
+    }
 }
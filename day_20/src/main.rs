@@ -2,11 +2,37 @@
 #![allow(unused_variables, dead_code)]
 
 use aoc_helpers::data_loader::DataLoader;
+use aoc_helpers::logging;
+use std::env;
+use std::process;
 
 mod solver;
-use crate::solver::{solve_a, solve_b};
+use crate::solver::{dump_rounds, grove_coordinates, solve_a, solve_b, MixConfig};
 
 fn main() {
+    let args = env::args()
+        .skip(1)
+        .filter(|arg| arg.starts_with("--"))
+        .collect::<Vec<String>>();
+    if !args.is_empty() {
+        let input = match logging::positional_args().first() {
+            Some(path) => DataLoader::from_file(path),
+            None => DataLoader::from_file("data/puzzle_input.txt"),
+        };
+        let config = match MixConfig::part_a().with_args(args) {
+            Ok(config) => config,
+            Err(why) => {
+                eprintln!("{why}");
+                process::exit(1);
+            }
+        };
+        if config.dump {
+            println!("{}\n", dump_rounds(&input, &config).unwrap());
+        }
+        println!("mix: {}", grove_coordinates(&input, &config).unwrap());
+        return;
+    }
+
     let a_input = DataLoader::from_file("data/puzzle_input.txt");
     println!("a: {}", solve_a(&a_input).unwrap());
     println!("b: {}", solve_b(&a_input).unwrap());
@@ -1,19 +1,53 @@
 use aoc_helpers::data_loader::DataLoader;
+use itertools::Itertools;
+
+use std::cmp::Ordering;
 
 type Val = i64;
 type Idx = usize;
 type OriginIdx = usize;
 
+#[derive(Debug, Clone)]
+struct Node {
+    val: Val,
+    prio: u64,
+    size: usize,
+    left: Option<OriginIdx>,
+    right: Option<OriginIdx>,
+    parent: Option<OriginIdx>,
+}
+
+// implicit treap, nodes are addressed by their original index
 #[derive(Debug, Clone)]
 struct CircBuf {
-    buf: Vec<(Val, OriginIdx)>,
+    nodes: Vec<Node>,
+    root: Option<OriginIdx>,
 }
 
 impl CircBuf {
     fn new(values: Vec<Val>) -> CircBuf {
-        CircBuf {
-            buf: Vec::from_iter(values.into_iter().enumerate().map(|(i, v)| (v, i))),
+        let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+        let mut res = CircBuf {
+            nodes: vec![],
+            root: None,
+        };
+
+        for (i, val) in values.into_iter().enumerate() {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            res.nodes.push(Node {
+                val,
+                prio: seed,
+                size: 1,
+                left: None,
+                right: None,
+                parent: None,
+            });
+            res.root = res.merge(res.root, Some(i));
         }
+        res.detach_root();
+        res
     }
 
     fn from_loader(loader: &DataLoader) -> CircBuf {
@@ -22,15 +56,107 @@ impl CircBuf {
         ))
     }
 
+    fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    fn size(&self, node: Option<OriginIdx>) -> usize {
+        node.map_or(0, |i| self.nodes[i].size)
+    }
+
+    fn update(&mut self, i: OriginIdx) {
+        let (left, right) = (self.nodes[i].left, self.nodes[i].right);
+        self.nodes[i].size = 1 + self.size(left) + self.size(right);
+        for child in [left, right].into_iter().flatten() {
+            self.nodes[child].parent = Some(i);
+        }
+    }
+
+    fn detach_root(&mut self) {
+        if let Some(root) = self.root {
+            self.nodes[root].parent = None;
+        }
+    }
+
+    // splits off the first `k` elements
+    fn split(
+        &mut self,
+        node: Option<OriginIdx>,
+        k: usize,
+    ) -> (Option<OriginIdx>, Option<OriginIdx>) {
+        let i = match node {
+            Some(i) => i,
+            None => return (None, None),
+        };
+
+        let left_size = self.size(self.nodes[i].left);
+        if k <= left_size {
+            let (l, r) = self.split(self.nodes[i].left, k);
+            self.nodes[i].left = r;
+            self.update(i);
+            (l, Some(i))
+        } else {
+            let (l, r) = self.split(self.nodes[i].right, k - left_size - 1);
+            self.nodes[i].right = l;
+            self.update(i);
+            (Some(i), r)
+        }
+    }
+
+    fn merge(&mut self, a: Option<OriginIdx>, b: Option<OriginIdx>) -> Option<OriginIdx> {
+        let (a, b) = match (a, b) {
+            (Some(a), Some(b)) => (a, b),
+            (a, None) => return a,
+            (None, b) => return b,
+        };
+
+        if self.nodes[a].prio > self.nodes[b].prio {
+            self.nodes[a].right = self.merge(self.nodes[a].right, Some(b));
+            self.update(a);
+            Some(a)
+        } else {
+            self.nodes[b].left = self.merge(Some(a), self.nodes[b].left);
+            self.update(b);
+            Some(b)
+        }
+    }
+
+    fn get_idx(&self, origin_idx: OriginIdx) -> Idx {
+        let mut res = self.size(self.nodes[origin_idx].left);
+        let mut current = origin_idx;
+        while let Some(parent) = self.nodes[current].parent {
+            if self.nodes[parent].right == Some(current) {
+                res += self.size(self.nodes[parent].left) + 1;
+            }
+            current = parent;
+        }
+        res
+    }
+
+    fn get_val(&self, mut idx: Idx) -> Val {
+        let mut current = self.root.unwrap();
+        loop {
+            let left_size = self.size(self.nodes[current].left);
+            match idx.cmp(&left_size) {
+                Ordering::Less => current = self.nodes[current].left.unwrap(),
+                Ordering::Equal => return self.nodes[current].val,
+                Ordering::Greater => {
+                    idx -= left_size + 1;
+                    current = self.nodes[current].right.unwrap();
+                }
+            }
+        }
+    }
+
     fn mix_idx(&mut self, origin_idx: OriginIdx) {
         let idx = self.get_idx(origin_idx);
-        self.move_elem(idx, self.buf[idx].0);
+        self.move_elem(idx, self.nodes[origin_idx].val);
     }
 
     fn mixed_buf(&self) -> CircBuf {
         let mut res = self.clone();
 
-        for origin_idx in 0..self.buf.len() {
+        for origin_idx in 0..self.len() {
             res.mix_idx(origin_idx);
         }
 
@@ -38,79 +164,244 @@ impl CircBuf {
     }
 
     fn move_elem(&mut self, idx: Idx, by: Val) {
-        let circ = (self.buf.len() * 2) as i64;
-
-        if by > 0 {
-            self.bubble_elem_forward(idx, by % circ);
-        } else if by < 0 {
-            self.buf.reverse();
-            self.bubble_elem_forward(self.buf.len() - idx - 1, by.abs() % circ);
-            self.buf.reverse();
+        // while moving, the element itself is not part of the circle
+        let circ = self.len() as i64 - 1;
+        if circ <= 0 {
+            return;
         }
+
+        let (before, rest) = self.split(self.root, idx);
+        let (elem, after) = self.split(rest, 1);
+        self.root = self.merge(before, after);
+        self.detach_root();
+
+        let target = (idx as i64 + by).rem_euclid(circ) as usize;
+        let (before, after) = self.split(self.root, target);
+        let before = self.merge(before, elem);
+        self.root = self.merge(before, after);
+        self.detach_root();
     }
 
-    fn bubble_elem_forward(&mut self, idx: Idx, by: Val) {
-        if by == 0 {
-            return;
+    fn view(&self) -> Vec<i64> {
+        let mut res = vec![];
+        let mut stack = vec![];
+        let mut current = self.root;
+
+        while current.is_some() || !stack.is_empty() {
+            while let Some(i) = current {
+                stack.push(i);
+                current = self.nodes[i].left;
+            }
+            let i = stack.pop().unwrap();
+            res.push(self.nodes[i].val);
+            current = self.nodes[i].right;
         }
+        res
+    }
 
-        let c_idx = idx % self.buf.len();
-        let cnext_idx = (idx + 1) % self.buf.len();
+    fn view_from_0(&self) -> Option<Vec<Val>> {
+        let view = self.view();
+        let idx = view.iter().position(|v| *v == 0)?;
+        Some(
+            view[idx..]
+                .iter()
+                .chain(view[..idx].iter())
+                .copied()
+                .collect(),
+        )
+    }
 
-        self.buf.swap(c_idx, cnext_idx);
-        self.bubble_elem_forward(idx + 1, by - 1);
+    fn nth_val_after_0(&self, nth: usize) -> Option<Val> {
+        let origin_idx = self.nodes.iter().position(|node| node.val == 0)?;
+        let idx = self.get_idx(origin_idx);
+        Some(self.get_val((idx + nth) % self.len()))
     }
+}
 
-    fn get_idx(&self, origin_idx: OriginIdx) -> Idx {
-        self.buf.iter().position(|(_, i)| i == &origin_idx).unwrap()
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MixConfig {
+    pub key: Val,
+    pub rounds: usize,
+    pub offsets: Vec<usize>,
+    pub dump: bool,
+}
+
+impl MixConfig {
+    pub fn part_a() -> MixConfig {
+        MixConfig {
+            key: 1,
+            rounds: 1,
+            offsets: vec![1000, 2000, 3000],
+            dump: false,
+        }
     }
 
-    fn view(&self) -> Vec<i64> {
-        self.buf
-            .iter()
-            .map(|(v, _)| v)
-            .cloned()
-            .collect::<Vec<i64>>()
+    pub fn part_b() -> MixConfig {
+        MixConfig {
+            key: 811589153,
+            rounds: 10,
+            ..MixConfig::part_a()
+        }
     }
 
-    fn nth_val_after_0(&self, nth: usize) -> Val {
-        let idx = self.buf.iter().position(|(v, _)| v == &0).unwrap();
-        self.buf[(idx + nth) % self.buf.len()].0
+    pub fn with_args<I: IntoIterator<Item = String>>(
+        mut self,
+        args: I,
+    ) -> Result<MixConfig, String> {
+        let invalid = |arg: &str| format!("invalid option {arg}");
+
+        for arg in args {
+            match arg.split_once('=') {
+                Some(("--key", val)) => self.key = val.parse().map_err(|_| invalid(&arg))?,
+                Some(("--rounds", val)) => self.rounds = val.parse().map_err(|_| invalid(&arg))?,
+                Some(("--offsets", val)) => {
+                    self.offsets = val
+                        .split(',')
+                        .map(|offset| offset.parse())
+                        .collect::<Result<Vec<usize>, _>>()
+                        .map_err(|_| invalid(&arg))?
+                }
+                None if arg == "--dump" => self.dump = true,
+                _ if arg.starts_with("--") => return Err(format!("unknown option {arg}")),
+                _ => {}
+            }
+        }
+        Ok(self)
     }
 }
 
-pub fn solve_a(loader: &DataLoader) -> Result<String, &str> {
-    let buf = CircBuf::from_loader(loader);
-    let mixed_buf = buf.mixed_buf();
+fn mix_rounds(loader: &DataLoader, config: &MixConfig) -> Vec<CircBuf> {
+    let values = loader
+        .iter()
+        .map(|line| line.parse::<Val>().unwrap() * config.key);
+    let mut res = vec![CircBuf::new(values.collect())];
 
-    let mut res = 0;
-    for nth in [1000, 2000, 3000] {
-        res += mixed_buf.nth_val_after_0(nth);
+    for round in 1..=config.rounds {
+        res.push(res.last().unwrap().mixed_buf());
+    }
+    res
+}
+
+pub fn dump_rounds(loader: &DataLoader, config: &MixConfig) -> Result<String, &'static str> {
+    let rounds = mix_rounds(loader, config);
+    let join = |values: Vec<Val>| values.iter().join(", ");
+
+    let mut res = vec!["Initial arrangement:".to_string(), join(rounds[0].view())];
+    for (round, buf) in rounds.iter().enumerate().skip(1) {
+        let plural = if round == 1 { "" } else { "s" };
+        res.push(String::new());
+        res.push(format!("After {round} round{plural} of mixing:"));
+        res.push(join(buf.view_from_0().ok_or("no 0 in buffer")?));
     }
+    Ok(res.join("\n"))
+}
+
+pub fn grove_coordinates(loader: &DataLoader, config: &MixConfig) -> Result<String, &'static str> {
+    let buf = mix_rounds(loader, config).pop().unwrap();
 
+    let mut res = 0;
+    for nth in config.offsets.iter() {
+        res += buf.nth_val_after_0(*nth).ok_or("no 0 in buffer")?;
+    }
     Ok(res.to_string())
 }
 
+pub fn solve_a(loader: &DataLoader) -> Result<String, &str> {
+    grove_coordinates(loader, &MixConfig::part_a())
+}
+
 pub fn solve_b(loader: &DataLoader) -> Result<String, &str> {
-    let input_buf = CircBuf::from_loader(loader);
-    let salted_buf = CircBuf::new(
-        input_buf
-            .view()
-            .into_iter()
-            .map(|v| v * 811589153)
-            .collect::<Vec<i64>>(),
-    );
+    grove_coordinates(loader, &MixConfig::part_b())
+}
 
-    let mut buf = salted_buf;
-    for i in 1..=10 {
-        let tmp_buf = buf.mixed_buf();
-        buf = tmp_buf;
+#[cfg(test)]
+mod test_main {
+    use super::*;
+
+    #[test]
+    fn mix_test() {
+        let buf = CircBuf::from_loader(&DataLoader::from_file("data/test_input.txt"));
+        assert_eq!(buf.view(), vec![1, 2, -3, 3, -2, 0, 4]);
+        assert_eq!(
+            buf.mixed_buf().view_from_0().unwrap(),
+            vec![0, 3, -2, 1, 2, -3, 4]
+        );
     }
 
-    let mut res = 0;
-    for nth in [1000, 2000, 3000] {
-        let tmp = buf.nth_val_after_0(nth);
-        res += tmp
+    #[test]
+    fn move_wraps_test() {
+        let buf = CircBuf::new(vec![0, 10, 8, -15]);
+        assert_eq!(buf.mixed_buf().view_from_0().unwrap(), vec![0, 10, -15, 8]);
+
+        // moving by a multiple of len - 1 leaves the order unchanged
+
+        let mut buf = CircBuf::new(vec![0, 1, 2, 3, 4]);
+        buf.move_elem(1, 8);
+        assert_eq!(buf.view(), vec![0, 1, 2, 3, 4]);
+        buf.move_elem(1, -1);
+        assert_eq!(buf.view(), vec![1, 0, 2, 3, 4]);
+        assert_eq!(buf.get_idx(1), 0);
+        assert_eq!(buf.nth_val_after_0(6), Some(2));
+    }
+
+    #[test]
+    fn config_args_test() {
+        let args = ["--key=3", "--rounds=2", "--offsets=1,3", "-v", "--dump"];
+        let config = MixConfig::part_a().with_args(args.map(|arg| arg.to_string()));
+        assert_eq!(
+            config,
+            Ok(MixConfig {
+                key: 3,
+                rounds: 2,
+                offsets: vec![1, 3],
+                dump: true,
+            })
+        );
+        assert_eq!(
+            MixConfig::part_a().with_args(["--rounds=x".to_string()]),
+            Err("invalid option --rounds=x".to_string())
+        );
+        assert_eq!(
+            MixConfig::part_a().with_args(["--round=3".to_string()]),
+            Err("unknown option --round=3".to_string())
+        );
+
+        let loader = DataLoader::from_file("data/test_input.txt");
+        let config = MixConfig::part_a().with_args(args.map(|arg| arg.to_string()));
+        assert_eq!(
+            grove_coordinates(&loader, &config.unwrap()),
+            Ok("3".to_string())
+        );
+    }
+
+    #[test]
+    fn dump_rounds_test() {
+        let loader = DataLoader::from_file("data/test_input.txt");
+        let dump = dump_rounds(&loader, &MixConfig::part_b()).unwrap();
+        let lines = dump.lines().collect::<Vec<&str>>();
+
+        assert_eq!(lines.len(), 2 + 10 * 3);
+        assert_eq!(
+            lines[0..2],
+            [
+                "Initial arrangement:",
+                "811589153, 1623178306, -2434767459, 2434767459, -1623178306, 0, 3246356612",
+            ]
+        );
+        assert_eq!(
+            lines[2..8],
+            [
+                "",
+                "After 1 round of mixing:",
+                "0, -2434767459, 3246356612, -1623178306, 2434767459, 1623178306, 811589153",
+                "",
+                "After 2 rounds of mixing:",
+                "0, 2434767459, 1623178306, 3246356612, -2434767459, -1623178306, 811589153",
+            ]
+        );
+        assert_eq!(
+            lines[31],
+            "0, -2434767459, 1623178306, 3246356612, -1623178306, 2434767459, 811589153"
+        );
     }
-    Ok(res.to_string())
 }
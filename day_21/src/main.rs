@@ -4,10 +4,21 @@
 use aoc_helpers::data_loader::DataLoader;
 
 mod solver;
-use crate::solver::{solve_a, solve_b};
+use crate::solver::{diagnose, root_dot, root_formula, solve_a, solve_b};
+use std::env;
 
 fn main() {
     let a_input = DataLoader::from_file("data/puzzle_input.txt");
+    if let Some(diagnostics) = diagnose(&a_input).ok().filter(|d| !d.is_empty()) {
+        eprint!("{}", diagnostics);
+    }
+    if env::args().any(|arg| arg == "--formula") {
+        println!("{}", root_formula(&a_input).unwrap());
+    }
+    if env::args().any(|arg| arg == "--dot") {
+        println!("{}", root_dot(&a_input).unwrap());
+        return;
+    }
     println!("a: {}", solve_a(&a_input).unwrap());
     println!("b: {}", solve_b(&a_input).unwrap());
 }
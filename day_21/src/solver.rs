@@ -1,7 +1,9 @@
 use aoc_helpers::data_loader::DataLoader;
+use aoc_helpers::interner::{Id, Interner};
+use aoc_helpers::rational::Rational;
 use itertools::Itertools;
 
-use std::collections::{HashMap, VecDeque};
+use std::fmt;
 
 #[derive(Debug, Clone, Copy)]
 enum Op {
@@ -21,58 +23,99 @@ impl Op {
             _ => panic!("unknown operation"),
         }
     }
+
+    fn symbol(&self) -> char {
+        match self {
+            Op::Add => '+',
+            Op::Sub => '-',
+            Op::Mul => '*',
+            Op::Div => '/',
+        }
+    }
+
+    fn precedence(&self) -> u8 {
+        match self {
+            Op::Add | Op::Sub => 1,
+            Op::Mul | Op::Div => 2,
+        }
+    }
 }
 
-type Monkey = String;
+type Monkey = Id;
 
-#[derive(Debug, Clone)]
-struct Equation {
-    res: Monkey,
-    left: Monkey,
-    op: Op,
-    right: Monkey,
+#[derive(Debug, Clone, Copy)]
+enum Job {
+    Num(i64),
+    Op(Monkey, Op, Monkey),
 }
 
-impl Equation {
-    fn get_res(&self, left: i64, right: i64) -> i64 {
-        match self.op {
-            Op::Add => left + right,
-            Op::Sub => left - right,
-            Op::Mul => left * right,
-            Op::Div => left / right,
+// a * x + b
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Linear {
+    a: Rational,
+    b: Rational,
+}
+
+impl Linear {
+    fn constant(b: Rational) -> Linear {
+        Linear {
+            a: Rational::zero(),
+            b,
         }
     }
 
-    fn get_left(&self, res: i64, right: i64) -> i64 {
-        match self.op {
-            Op::Add => res - right,
-            Op::Sub => res + right,
-            Op::Mul => res / right,
-            Op::Div => res * right,
+    fn variable() -> Linear {
+        Linear {
+            a: Rational::one(),
+            b: Rational::zero(),
         }
     }
 
-    fn get_right(&self, res: i64, left: i64) -> i64 {
-        match self.op {
-            Op::Add => res - left,
-            Op::Sub => left - res,
-            Op::Mul => res / left,
-            Op::Div => left / res,
+    fn is_constant(&self) -> bool {
+        self.a.is_zero()
+    }
+
+    fn scale(&self, factor: Rational) -> Linear {
+        Linear {
+            a: self.a * factor,
+            b: self.b * factor,
+        }
+    }
+
+    fn apply(self, op: Op, rhs: Linear) -> Result<Linear, &'static str> {
+        match op {
+            Op::Add => Ok(Linear {
+                a: self.a + rhs.a,
+                b: self.b + rhs.b,
+            }),
+            Op::Sub => Ok(Linear {
+                a: self.a - rhs.a,
+                b: self.b - rhs.b,
+            }),
+            Op::Mul if self.is_constant() => Ok(rhs.scale(self.b)),
+            Op::Mul if rhs.is_constant() => Ok(self.scale(rhs.b)),
+            Op::Div if rhs.is_constant() => {
+                let factor = Rational::one()
+                    .checked_div(rhs.b)
+                    .ok_or("division by zero")?;
+                Ok(self.scale(factor))
+            }
+            _ => Err("equation is not linear"),
         }
     }
 }
 
 #[derive(Debug)]
 struct Context {
-    lookup: HashMap<Monkey, i64>,
-    equations: VecDeque<Equation>,
+    names: Interner,
+    jobs: Vec<Option<Job>>,
 }
 
 impl Context {
     fn new() -> Context {
         Context {
-            lookup: HashMap::new(),
-            equations: VecDeque::new(),
+            names: Interner::new(),
+            jobs: vec![],
         }
     }
 
@@ -81,104 +124,513 @@ impl Context {
 
         for line in loader.iter() {
             let split = line.split(' ').collect_vec();
+            let name = ctx.names.intern(split[0].trim_end_matches(':'));
 
-            if split.len() == 4 {
-                let res = split[0].replace(":", "");
-                let left = split[1].to_string();
+            let job = if split.len() == 4 {
+                let left = ctx.names.intern(split[1]);
                 let op = Op::from_char(split[2].chars().next().unwrap());
-                let right = split[3].to_string();
-                ctx.equations.push_back(Equation {
-                    res,
-                    left,
-                    op,
-                    right,
-                });
+                let right = ctx.names.intern(split[3]);
+                Job::Op(left, op, right)
             } else if split.len() == 2 {
-                let name = split[0].replace(":", "");
-                let num = split[1].parse::<i64>().unwrap();
-                ctx.lookup.insert(name, num);
+                Job::Num(split[1].parse::<i64>().unwrap())
             } else {
                 panic!("line not parable");
-            }
+            };
+            ctx.insert(name, job);
         }
         ctx
     }
 
-    fn solve_upwards(&mut self) {
-        let mut abort_counter = 0;
+    fn monkey(&self, name: &str) -> Option<Monkey> {
+        self.names.get(name)
+    }
 
-        while abort_counter < self.equations.len() && let Some(eq) = self.equations.pop_front() {
-            if let Some(left) = self.lookup.get(&eq.left)
-                && let Some(right) = self.lookup.get(&eq.right) {
-                let res = eq.get_res(*left, *right);
+    fn job(&self, monkey: Monkey) -> Result<Job, &'static str> {
+        self.jobs
+            .get(monkey as usize)
+            .copied()
+            .flatten()
+            .ok_or("monkey has no job")
+    }
 
-                self.lookup.insert(eq.res.clone(), res);
-                abort_counter = 0;
-            } else {
-                self.equations.push_back(eq);
-                abort_counter += 1;
+    fn insert(&mut self, monkey: Monkey, job: Job) {
+        if monkey as usize >= self.jobs.len() {
+            self.jobs.resize(monkey as usize + 1, None);
+        }
+        self.jobs[monkey as usize] = Some(job);
+    }
+
+    fn deps(&self, monkey: Monkey) -> Vec<Monkey> {
+        match self.job(monkey) {
+            Ok(Job::Op(left, _, right)) => vec![left, right],
+            _ => vec![],
+        }
+    }
+
+    fn visit(
+        &self,
+        start: Monkey,
+        marks: &mut [Mark],
+        order: &mut Vec<Monkey>,
+        cycles: &mut Vec<Vec<Monkey>>,
+    ) {
+        let mut stack = vec![(start, 0)];
+        marks[start as usize] = Mark::Active;
+
+        while let Some(&(monkey, idx)) = stack.last() {
+            match self.deps(monkey).get(idx) {
+                Some(&next) => {
+                    stack.last_mut().unwrap().1 += 1;
+                    match marks[next as usize] {
+                        Mark::New => {
+                            marks[next as usize] = Mark::Active;
+                            stack.push((next, 0));
+                        }
+                        Mark::Active => {
+                            let pos = stack.iter().position(|(m, _)| *m == next).unwrap();
+                            let path = stack[pos..].iter().map(|(m, _)| *m);
+                            cycles.push(path.chain([next]).collect());
+                        }
+                        Mark::Done => {}
+                    }
+                }
+                None => {
+                    marks[monkey as usize] = Mark::Done;
+                    order.push(monkey);
+                    stack.pop();
+                }
             }
         }
     }
 
-    fn solve_downwards(&mut self) {
-        let mut abort_counter = 0;
+    fn analyze(&self, root: Monkey) -> Diagnostics {
+        let monkeys = 0..self.names.len() as Monkey;
+        let mut marks = vec![Mark::New; self.names.len()];
+        let mut cycles = vec![];
 
-        while abort_counter < self.equations.len() && let Some(eq) = self.equations.pop_front() {
-            if let Some(res) = self.lookup.get(&eq.res) {
-                if let Some(left) = self.lookup.get(&eq.left) {
-                    let right = eq.get_right(*res, *left);
-                    self.lookup.insert(eq.right.clone(), right);
-                    abort_counter = 0;
-                    continue;
-                } else if let Some(right) = self.lookup.get(&eq.right) {
-                    let left = eq.get_left(*res, *right);
-                    self.lookup.insert(eq.left.clone(), left);
-                    abort_counter = 0;
-                    continue;
+        self.visit(root, &mut marks, &mut vec![], &mut cycles);
+        let unreachable = monkeys
+            .clone()
+            .filter(|m| marks[*m as usize] == Mark::New && self.job(*m).is_ok())
+            .collect_vec();
+
+        for monkey in monkeys.clone() {
+            if marks[monkey as usize] == Mark::New {
+                self.visit(monkey, &mut marks, &mut vec![], &mut cycles);
+            }
+        }
+
+        let name = |m: &Monkey| self.names.resolve(*m).to_string();
+        Diagnostics {
+            undefined: monkeys
+                .filter(|m| self.job(*m).is_err())
+                .map(|m| name(&m))
+                .collect(),
+            cycles: cycles
+                .iter()
+                .map(|cycle| cycle.iter().map(name).collect())
+                .collect(),
+            unreachable: unreachable.iter().map(name).collect(),
+        }
+    }
+
+    fn topo_order(&self, root: Monkey) -> Result<Vec<Monkey>, &'static str> {
+        let mut marks = vec![Mark::New; self.names.len()];
+        let mut order = vec![];
+        let mut cycles = vec![];
+        self.visit(root, &mut marks, &mut order, &mut cycles);
+
+        if order.iter().any(|m| self.job(*m).is_err()) {
+            Err("undefined monkey referenced")
+        } else if !cycles.is_empty() {
+            Err("monkey graph contains a cycle")
+        } else {
+            Ok(order)
+        }
+    }
+
+    fn linear(
+        &self,
+        order: &[Monkey],
+        var: Option<Monkey>,
+    ) -> Result<Vec<Option<Linear>>, &'static str> {
+        let mut res: Vec<Option<Linear>> = vec![None; self.names.len()];
+
+        for &monkey in order {
+            let val = if Some(monkey) == var {
+                Linear::variable()
+            } else {
+                match self.job(monkey)? {
+                    Job::Num(num) => Linear::constant(Rational::from(num)),
+                    Job::Op(left, op, right) => {
+                        let get = |m: Monkey| res[m as usize].ok_or("monkey not evaluated yet");
+                        get(left)?.apply(op, get(right)?)?
+                    }
                 }
+            };
+            res[monkey as usize] = Some(val);
+        }
+        Ok(res)
+    }
+
+    fn expr(&self, monkey: Monkey, var: Option<Monkey>) -> Result<Expr, &'static str> {
+        if Some(monkey) == var {
+            return Ok(Expr::Var(self.names.resolve(monkey).to_string()));
+        }
+
+        match self.job(monkey)? {
+            Job::Num(num) => Ok(Expr::Num(Rational::from(num))),
+            Job::Op(left, op, right) => match (self.expr(left, var)?, self.expr(right, var)?) {
+                (Expr::Num(left), Expr::Num(right)) => {
+                    let res = Linear::constant(left).apply(op, Linear::constant(right))?;
+                    Ok(Expr::Num(res.b))
+                }
+                (left, right) => Ok(Expr::Bin(Box::new(left), op, Box::new(right))),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Num(Rational),
+    Var(String),
+    Bin(Box<Expr>, Op, Box<Expr>),
+}
+
+impl Expr {
+    fn precedence(&self) -> u8 {
+        match self {
+            Expr::Num(num) if !num.is_integer() => 0,
+            Expr::Num(_) | Expr::Var(_) => 3,
+            Expr::Bin(_, op, _) => op.precedence(),
+        }
+    }
+
+    fn label(&self) -> String {
+        match self {
+            Expr::Num(num) => num.to_string(),
+            Expr::Var(name) => name.clone(),
+            Expr::Bin(_, op, _) => op.symbol().to_string(),
+        }
+    }
+
+    fn infix(&self) -> String {
+        match self {
+            Expr::Bin(left, op, right) => {
+                let wrap = |expr: &Expr, needs_parens: bool| match needs_parens {
+                    true => format!("({})", expr.infix()),
+                    false => expr.infix(),
+                };
+                let left_parens = left.precedence() < op.precedence();
+                let right_parens = right.precedence() < op.precedence()
+                    || (right.precedence() == op.precedence() && matches!(op, Op::Sub | Op::Div));
+                format!(
+                    "{} {} {}",
+                    wrap(left, left_parens),
+                    op.symbol(),
+                    wrap(right, right_parens)
+                )
             }
-            self.equations.push_back(eq);
-            abort_counter += 1;
+            _ => self.label(),
+        }
+    }
+
+    fn children(&self) -> Vec<&Expr> {
+        match self {
+            Expr::Bin(left, _, right) => vec![left, right],
+            _ => vec![],
+        }
+    }
+
+    fn write_dot(&self, id: usize, res: &mut Vec<String>) -> usize {
+        res.push(format!("    n{id} [label=\"{}\"];", self.label()));
+        let mut next = id + 1;
+        for child in self.children() {
+            res.push(format!("    n{id} -> n{next};"));
+            next = child.write_dot(next, res);
+        }
+        next
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mark {
+    New,
+    Active,
+    Done,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Diagnostics {
+    pub undefined: Vec<String>,
+    pub cycles: Vec<Vec<String>>,
+    pub unreachable: Vec<String>,
+}
+
+impl Diagnostics {
+    pub fn is_empty(&self) -> bool {
+        self.undefined.is_empty() && self.cycles.is_empty() && self.unreachable.is_empty()
+    }
+}
+
+impl fmt::Display for Diagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.undefined.is_empty() {
+            writeln!(f, "undefined monkeys: {}", self.undefined.join(", "))?;
+        }
+        for cycle in self.cycles.iter() {
+            writeln!(f, "cycle: {}", cycle.join(" -> "))?;
         }
+        if !self.unreachable.is_empty() {
+            writeln!(f, "unreachable from root: {}", self.unreachable.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+pub fn diagnose(loader: &DataLoader) -> Result<Diagnostics, &str> {
+    let ctx = Context::from_loader(loader);
+    let root = ctx.monkey("root").ok_or("root not found")?;
+    Ok(ctx.analyze(root))
+}
+
+fn root_equation(loader: &DataLoader) -> Result<(Expr, Expr), &str> {
+    let ctx = Context::from_loader(loader);
+    let root = ctx.monkey("root").ok_or("root not found")?;
+    let humn = ctx.monkey("humn");
+    ctx.topo_order(root)?;
+
+    match ctx.job(root)? {
+        Job::Op(left, _, right) => Ok((ctx.expr(left, humn)?, ctx.expr(right, humn)?)),
+        Job::Num(_) => Err("root eq could not be resolved"),
     }
 }
 
+pub fn root_formula(loader: &DataLoader) -> Result<String, &str> {
+    let (left, right) = root_equation(loader)?;
+    Ok(format!("{} = {}", left.infix(), right.infix()))
+}
+
+pub fn root_dot(loader: &DataLoader) -> Result<String, &str> {
+    let (left, right) = root_equation(loader)?;
+    let mut lines = vec![
+        "digraph root {".to_string(),
+        "    n0 [label=\"=\"];".to_string(),
+    ];
+    lines.push("    n0 -> n1;".to_string());
+    let next = left.write_dot(1, &mut lines);
+    lines.push(format!("    n0 -> n{next};"));
+    right.write_dot(next, &mut lines);
+    lines.push("}".to_string());
+    Ok(lines.join("\n"))
+}
+
 pub fn solve_a(loader: &DataLoader) -> Result<String, &str> {
-    let mut ctx = Context::from_loader(loader);
-    ctx.solve_upwards();
-    let root = ctx.lookup.get("root").ok_or("root not found")?;
-    Ok(root.to_string())
+    let ctx = Context::from_loader(loader);
+    let root = ctx.monkey("root").ok_or("root not found")?;
+    let order = ctx.topo_order(root)?;
+    let res = ctx.linear(&order, None)?[root as usize].ok_or("root not found")?;
+    let res = res.b.to_integer().ok_or("root is not an integer")?;
+    Ok(res.to_string())
 }
 
 pub fn solve_b(loader: &DataLoader) -> Result<String, &str> {
-    let mut ctx = Context::from_loader(loader);
-    let root_eq;
-    {
-        let idx = ctx
-            .equations
-            .iter()
-            .position(|eq| eq.res == "root")
-            .unwrap();
-        root_eq = ctx.equations[idx].clone();
-        ctx.equations.remove(idx);
-        ctx.lookup.remove("humn");
-    }
-
-    ctx.solve_upwards();
-
-    {
-        if let Some(left) = ctx.lookup.get(&root_eq.left) {
-            ctx.lookup.insert(root_eq.right, *left);
-        } else if let Some(right) = ctx.lookup.get(&root_eq.right) {
-            ctx.lookup.insert(root_eq.left, *right);
-        } else {
-            return Err("root eq could not be resolv;ed");
-        }
+    let ctx = Context::from_loader(loader);
+    let root = ctx.monkey("root").ok_or("root not found")?;
+    let humn = ctx.monkey("humn").ok_or("humn not found")?;
+
+    let (left, right) = match ctx.job(root)? {
+        Job::Op(left, _, right) => (left, right),
+        Job::Num(_) => return Err("root eq could not be resolved"),
+    };
+    let order = ctx.topo_order(root)?;
+    let values = ctx.linear(&order, Some(humn))?;
+    let left = values[left as usize].ok_or("left side not found")?;
+    let right = values[right as usize].ok_or("right side not found")?;
+
+    // left.a * x + left.b = right.a * x + right.b
+    let res = (right.b - left.b)
+        .checked_div(left.a - right.a)
+        .ok_or("equation has no unique solution")?;
+    let res = res.to_integer().ok_or("solution is not an integer")?;
+    Ok(res.to_string())
+}
+
+#[cfg(test)]
+mod test_main {
+    use super::*;
+
+    fn loader(lines: &[&str]) -> DataLoader {
+        DataLoader::from_data(&lines.iter().map(|line| line.to_string()).collect())
+    }
+
+    #[test]
+    fn exact_division_test() {
+        let input = loader(&[
+            "root: aaaa / bbbb",
+            "aaaa: cccc * dddd",
+            "bbbb: 4",
+            "cccc: 6",
+            "dddd: 2",
+        ]);
+        assert_eq!(solve_a(&input), Ok("3".to_string()));
+
+        let input = loader(&[
+            "root: aaaa * bbbb",
+            "aaaa: cccc / dddd",
+            "bbbb: 4",
+            "cccc: 3",
+            "dddd: 2",
+        ]);
+        assert_eq!(solve_a(&input), Ok("6".to_string()));
+    }
+
+    #[test]
+    fn solve_errors_test() {
+        let input = loader(&[
+            "root: aaaa + bbbb",
+            "aaaa: humn * humn",
+            "bbbb: 4",
+            "humn: 1",
+        ]);
+        assert_eq!(solve_b(&input), Err("equation is not linear"));
+
+        let input = loader(&[
+            "root: aaaa + bbbb",
+            "aaaa: humn * cccc",
+            "bbbb: 5",
+            "cccc: 2",
+            "humn: 1",
+        ]);
+        assert_eq!(solve_b(&input), Err("solution is not an integer"));
+
+        let input = loader(&[
+            "root: aaaa + bbbb",
+            "aaaa: cccc / humn",
+            "bbbb: 5",
+            "cccc: 2",
+            "humn: 1",
+        ]);
+        assert_eq!(solve_b(&input), Err("equation is not linear"));
+
+        let input = loader(&[
+            "root: aaaa + bbbb",
+            "aaaa: humn - humn",
+            "bbbb: 5",
+            "humn: 1",
+        ]);
+        assert_eq!(solve_b(&input), Err("equation has no unique solution"));
+
+        let input = loader(&[
+            "root: aaaa + bbbb",
+            "aaaa: humn / cccc",
+            "bbbb: 5",
+            "cccc: 2",
+            "humn: 1",
+        ]);
+        assert_eq!(solve_b(&input), Ok("10".to_string()));
     }
 
-    ctx.solve_downwards();
+    #[test]
+    fn diagnose_test() {
+        let input = loader(&[
+            "root: aaaa + bbbb",
+            "aaaa: cccc * xxxx",
+            "bbbb: 4",
+            "cccc: dddd - eeee",
+            "dddd: cccc / bbbb",
+            "eeee: 2",
+            "ffff: gggg + eeee",
+            "gggg: 3",
+        ]);
+        let diagnostics = diagnose(&input).unwrap();
+        assert_eq!(
+            diagnostics,
+            Diagnostics {
+                undefined: vec!["xxxx".to_string()],
+                cycles: vec![vec![
+                    "cccc".to_string(),
+                    "dddd".to_string(),
+                    "cccc".to_string()
+                ]],
+                unreachable: vec!["ffff".to_string(), "gggg".to_string()],
+            }
+        );
+        assert_eq!(
+            diagnostics.to_string(),
+            "undefined monkeys: xxxx\n\
+             cycle: cccc -> dddd -> cccc\n\
+             unreachable from root: ffff, gggg\n"
+        );
+        assert_eq!(solve_a(&input), Err("undefined monkey referenced"));
+
+        let input = loader(&[
+            "root: aaaa + bbbb",
+            "aaaa: bbbb * cccc",
+            "bbbb: aaaa - cccc",
+            "cccc: 2",
+        ]);
+        assert_eq!(solve_a(&input), Err("monkey graph contains a cycle"));
 
-    let humn = ctx.lookup.get("humn").ok_or("humn not found")?;
-    Ok(humn.to_string())
+        // problems root can not reach are reported but do not block solving
+        let input = loader(&[
+            "root: aaaa + bbbb",
+            "aaaa: 3",
+            "bbbb: 4",
+            "cccc: dddd * xxxx",
+            "dddd: cccc - aaaa",
+        ]);
+        assert!(!diagnose(&input).unwrap().is_empty());
+        assert_eq!(solve_a(&input), Ok("7".to_string()));
+        assert!(diagnose(&DataLoader::from_file("data/test_input.txt"))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn root_formula_test() {
+        let input = DataLoader::from_file("data/test_input.txt");
+        assert_eq!(
+            root_formula(&input),
+            Ok("(4 + 2 * (humn - 3)) / 4 = 150".to_string())
+        );
+
+        let input = loader(&[
+            "root: aaaa + bbbb",
+            "aaaa: cccc - dddd",
+            "bbbb: 7",
+            "cccc: 10",
+            "dddd: humn - eeee",
+            "eeee: ffff / gggg",
+            "ffff: 3",
+            "gggg: 2",
+            "humn: 5",
+        ]);
+        assert_eq!(
+            root_formula(&input),
+            Ok("10 - (humn - (3/2)) = 7".to_string())
+        );
+        assert_eq!(
+            root_dot(&input),
+            Ok([
+                "digraph root {",
+                "    n0 [label=\"=\"];",
+                "    n0 -> n1;",
+                "    n1 [label=\"-\"];",
+                "    n1 -> n2;",
+                "    n2 [label=\"10\"];",
+                "    n1 -> n3;",
+                "    n3 [label=\"-\"];",
+                "    n3 -> n4;",
+                "    n4 [label=\"humn\"];",
+                "    n3 -> n5;",
+                "    n5 [label=\"3/2\"];",
+                "    n0 -> n6;",
+                "    n6 [label=\"7\"];",
+                "}",
+            ]
+            .join("\n"))
+        );
+    }
 }
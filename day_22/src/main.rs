@@ -2,22 +2,37 @@
 #![allow(unused_variables, dead_code)]
 
 use aoc_helpers::data_loader::DataLoader;
+use aoc_helpers::logging;
+use std::env;
+
+mod world;
 
 mod solver_a;
-use crate::solver_a::solve_a;
+use crate::solver_a::{solve_a, trace_a};
 
 mod solver_b;
-use crate::solver_b::solve_b;
+use crate::solver_b::{solve_b, trace_b};
 
 fn main() {
+    logging::init();
+
     let a_input = DataLoader::from_file("data/puzzle_input.txt");
+    if env::args().any(|arg| arg == "--trace") {
+        let per_face = env::args().any(|arg| arg == "--faces");
+        println!("{}", trace_a(&a_input).unwrap());
+        println!("{}", trace_b(&a_input, per_face).unwrap());
+        return;
+    }
+
     println!("a: {}", solve_a(&a_input).unwrap());
-    println!("b: {}", solve_b(&a_input, 50).unwrap());
+    println!("b: {}", solve_b(&a_input).unwrap());
 }
 
 #[cfg(test)]
 mod test_main {
     use super::*;
+    use crate::solver_a::render_a;
+    use aoc_helpers::snapshot::assert_snapshot;
 
     #[test]
     fn a_test() {
@@ -30,8 +45,30 @@ mod test_main {
     #[test]
     fn b_test() {
         assert_eq!(
-            solve_b(&DataLoader::from_file("data/test_input.txt"), 4).unwrap(),
+            solve_b(&DataLoader::from_file("data/test_input.txt")).unwrap(),
             DataLoader::from_file("data/b_test_result.txt").test_result()
         );
     }
+
+    #[test]
+    fn a_snapshot_test() {
+        let loader = DataLoader::from_file("data/test_input.txt");
+        for instrs in [1, 4, 13] {
+            assert_snapshot(
+                &render_a(&loader, instrs).unwrap(),
+                &format!("data/a_test_snapshot_{instrs}.txt"),
+            );
+        }
+    }
+
+    #[test]
+    fn trace_snapshot_test() {
+        let loader = DataLoader::from_file("data/test_input.txt");
+        assert_snapshot(&trace_a(&loader).unwrap(), "data/a_test_trace.txt");
+        assert_snapshot(&trace_b(&loader, false).unwrap(), "data/b_test_trace.txt");
+        assert_snapshot(
+            &trace_b(&loader, true).unwrap(),
+            "data/b_test_trace_faces.txt",
+        );
+    }
 }
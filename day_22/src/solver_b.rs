@@ -1,411 +1,279 @@
+use crate::world::{board_from_loader, get_instrs, Board, Orient, Pos, World, WrapRule, ORIENTS};
 use aoc_helpers::data_loader::DataLoader;
-use aoc_helpers::matrix_helper::print_matrix;
-use simple_matrix::Matrix;
 use std::collections::HashMap;
 use std::collections::VecDeque;
-use std::iter;
+use tracing::trace;
 
-#[derive(Debug, Clone, Copy)]
-enum Orient {
-    Right,
-    Down,
-    Left,
-    Top,
+type Edge = Orient;
+type Quadrant = Pos;
+type RelativePos = Pos;
+type Vec3 = [i64; 3];
+type Gluing = HashMap<(Quadrant, Edge), (Quadrant, Edge)>;
+
+fn neg(v: Vec3) -> Vec3 {
+    [-v[0], -v[1], -v[2]]
 }
 
-impl Orient {
-    fn try_from(c: char) -> Option<Orient> {
-        match c {
-            'L' => Some(Orient::Left),
-            'R' => Some(Orient::Right),
-            _ => None,
-        }
-    }
+// orientation of a net face once the net is folded into a cube
+#[derive(Debug, Clone, Copy)]
+struct Face {
+    quad: Quadrant,
+    normal: Vec3,
+    right: Vec3,
+    down: Vec3,
+}
 
-    fn opposite(&self) -> Orient {
-        match self {
-            Orient::Top => Orient::Down,
-            Orient::Down => Orient::Top,
-            Orient::Left => Orient::Right,
-            Orient::Right => Orient::Left,
+impl Face {
+    fn dir(&self, edge: Edge) -> Vec3 {
+        match edge {
+            Edge::Right => self.right,
+            Edge::Down => self.down,
+            Edge::Left => neg(self.right),
+            Edge::Top => neg(self.down),
         }
     }
 
-    fn rotate(&self, orient: Orient) -> Orient {
-        match orient {
-            Orient::Right => match self {
-                Orient::Top => Orient::Right,
-                Orient::Right => Orient::Down,
-                Orient::Down => Orient::Left,
-                Orient::Left => Orient::Top,
+    fn neighbour(&self, edge: Edge) -> Face {
+        let quad = self.quad.next(edge);
+        match edge {
+            Edge::Right => Face {
+                quad,
+                normal: self.right,
+                right: neg(self.normal),
+                ..*self
+            },
+            Edge::Left => Face {
+                quad,
+                normal: neg(self.right),
+                right: self.normal,
+                ..*self
+            },
+            Edge::Down => Face {
+                quad,
+                normal: self.down,
+                down: neg(self.normal),
+                ..*self
+            },
+            Edge::Top => Face {
+                quad,
+                normal: neg(self.down),
+                down: self.normal,
+                ..*self
             },
-            Orient::Left => self
-                .rotate(Orient::Right)
-                .rotate(Orient::Right)
-                .rotate(Orient::Right),
-            _ => panic!("rotation to top or down not possible"),
-        }
-    }
-
-    fn to_char(&self) -> char {
-        match self {
-            Orient::Top => '^',
-            Orient::Right => '>',
-            Orient::Left => '<',
-            Orient::Down => 'v',
         }
     }
 }
 
-type Edge = Orient;
-
-#[derive(Debug, Clone, Copy)]
-enum Elem {
-    Wall,
-    Plain,
-}
-
-impl Elem {
-    fn try_from(c: char) -> Option<Elem> {
-        match c {
-            '.' => Some(Elem::Plain),
-            '#' => Some(Elem::Wall),
-            _ => None,
+fn fold_cube(quads: &[Quadrant]) -> Result<Gluing, &'static str> {
+    let start = *quads.iter().min().ok_or("empty map")?;
+    let mut faces = vec![Face {
+        quad: start,
+        normal: [0, 0, 1],
+        right: [1, 0, 0],
+        down: [0, 1, 0],
+    }];
+    let mut jobs = VecDeque::from([faces[0]]);
+
+    while let Some(face) = jobs.pop_front() {
+        for edge in ORIENTS {
+            let next = face.neighbour(edge);
+            if quads.contains(&next.quad) && faces.iter().all(|f| f.quad != next.quad) {
+                faces.push(next);
+                jobs.push_back(next);
+            }
         }
     }
-}
 
-#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
-struct Pos {
-    y: i64,
-    x: i64,
-}
-
-impl Pos {
-    fn next(&self, orient: Orient) -> Pos {
-        let mut res = *self;
-        match orient {
-            Orient::Right => res.x += 1,
-            Orient::Down => res.y += 1,
-            Orient::Left => res.x -= 1,
-            Orient::Top => res.y -= 1,
-        }
-        res
+    if faces.len() != 6 || quads.len() != 6 {
+        return Err("map is not a cube net");
     }
 
-    fn to_tuple(&self) -> (i64, i64) {
-        (self.x, self.y)
+    let mut res = HashMap::new();
+    for face in faces.iter() {
+        for edge in ORIENTS {
+            let other = faces
+                .iter()
+                .find(|f| f.normal == face.dir(edge))
+                .ok_or("map is not a cube net")?;
+            let other_edge = ORIENTS
+                .into_iter()
+                .find(|e| other.dir(*e) == face.normal)
+                .unwrap();
+            res.insert((face.quad, edge), (other.quad, other_edge));
+        }
     }
+    Ok(res)
 }
 
-type Quadrant = Pos;
-type RelativePos = Pos;
-
 #[derive(Debug)]
-struct World {
-    map: HashMap<Pos, Elem>,
-    pos: Pos,
-    orient: Orient,
+pub struct Cube {
     quadrant_size: i64,
+    gluing: Gluing,
 }
 
-impl World {
-    fn from_loader(loader: &DataLoader, quadrant_size: i64) -> World {
-        let mut map = HashMap::new();
-        loader
-            .iter()
-            .take_while(|line| !line.is_empty())
-            .enumerate()
-            .for_each(|(row, line)| {
-                line.chars()
-                    .enumerate()
-                    .filter_map(|(col, c)| {
-                        if let Some(elem) = Elem::try_from(c) {
-                            Some((col as i64, elem))
-                        } else {
-                            None
-                        }
-                    })
-                    .for_each(|(col, elem)| {
-                        map.insert(
-                            Pos {
-                                x: col,
-                                y: row as i64,
-                            },
-                            elem,
-                        );
-                    })
-            });
-        let pos = map.iter().map(|(pos, _)| *pos).min().unwrap();
-
-        World {
-            map,
-            pos,
-            orient: Orient::Right,
-            quadrant_size,
-        }
-    }
-
-    fn go(&mut self, steps: usize) {
-        if steps == 0 {
-            return;
-        }
-
-        let next_pos = self.pos.next(self.orient);
-        match self.map.get(&next_pos) {
-            None => {
-                self.print_map();
-                let (wrap_pos, wrap_orient) = self.next_pos_after_wrap_around();
-                match self.map.get(&wrap_pos) {
-                    None => panic!("should never happen"),
-                    Some(Elem::Wall) => {
-                        println!("----Wall----");
-                        return;
-                    }
-                    Some(Elem::Plain) => {
-                        self.pos = wrap_pos;
-                        self.orient = wrap_orient;
-                        self.print_map();
-                    }
-                }
-            }
-            Some(Elem::Wall) => return,
-            Some(Elem::Plain) => self.pos = next_pos,
+impl Cube {
+    pub fn fold(map: &Board) -> Result<Cube, &'static str> {
+        let quadrant_size = ((map.len() / 6) as f64).sqrt() as i64;
+        if quadrant_size == 0 || 6 * quadrant_size * quadrant_size != map.len() as i64 {
+            return Err("map is not a cube net");
         }
-
-        self.go(steps - 1);
+        let quads = map
+            .keys()
+            .filter(|pos| pos.x % quadrant_size == 0 && pos.y % quadrant_size == 0)
+            .map(|pos| Quadrant {
+                x: pos.x / quadrant_size,
+                y: pos.y / quadrant_size,
+            })
+            .collect::<Vec<Quadrant>>();
+        let gluing = fold_cube(&quads)?;
+
+        Ok(Cube {
+            quadrant_size,
+            gluing,
+        })
     }
 
-    fn rotate(&mut self, direction: Orient) {
-        self.orient = self.orient.rotate(direction);
+    pub fn quadrant_size(&self) -> i64 {
+        self.quadrant_size
     }
 
-    fn next_pos_after_wrap_around(&self) -> (Pos, Orient) {
-        let (quad, rel_pos) = self.get_current_quadrant_and_relative_pos();
-        let edge = self.get_current_quadrant_edge(rel_pos).unwrap();
-
-        //match (quad.to_tuple(), edge) {
-        //((2, 1), Edge::Right) => (
-        //Pos {
-        //x: (4 * self.quadrant_size) - (rel_pos.y + 1),
-        //y: (2 * self.quadrant_size),
-        //},
-        //Orient::Down,
-        //),
-        //((2, 2), Edge::Down) => (
-        //Pos {
-        //x: (1 * self.quadrant_size) - (rel_pos.x + 1),
-        //y: (2 * self.quadrant_size) - 1,
-        //},
-        //Orient::Top,
-        //),
-        //((1, 1), Edge::Top) => (
-        //Pos {
-        //x: (2 * self.quadrant_size),
-        //y: rel_pos.x,
-        //},
-        //Orient::Right,
-        //),
-        //_ => {
-        //println!("quad={:?}; rel_pos={:?}; edge={:?}", quad, rel_pos, edge);
-        //unimplemented!()
-        //}
-        //}
-
-        println!("quad={:?}; rel_pos={:?}; edge={:?}", quad, rel_pos, edge);
-        match (quad.to_tuple(), edge) {
-            ((1, 0), Edge::Top) => (
-                Pos {
-                    x: 0,
-                    y: (3 * self.quadrant_size) + rel_pos.x,
-                },
-                Orient::Right,
-            ),
-            ((0, 2), Edge::Left) => (
-                Pos {
-                    x: (1 * self.quadrant_size),
-                    y: (1 * self.quadrant_size) - (rel_pos.y + 1),
-                },
-                Orient::Right,
-            ),
-            ////
-            ((0, 2), Edge::Top) => (
-                Pos {
-                    x: (1 * self.quadrant_size),
-                    y: (1 * self.quadrant_size) + rel_pos.x,
-                },
-                Orient::Right,
-            ),
-            ((0, 3), Edge::Left) => (
-                Pos {
-                    x: (1 * self.quadrant_size) + rel_pos.y,
-                    y: 0,
-                },
-                Orient::Down,
-            ),
-            ((1, 2), Edge::Down) => (
-                Pos {
-                    x: (1 * self.quadrant_size) - 1,
-                    y: (3 * self.quadrant_size) + rel_pos.x,
-                },
-                Orient::Left,
-            ),
-            ((0, 3), Edge::Right) => (
-                Pos {
-                    x: (1 * self.quadrant_size) + rel_pos.y,
-                    y: (3 * self.quadrant_size) - 1,
-                },
-                Orient::Top,
-            ),
-            ((0, 3), Edge::Down) => (
-                Pos {
-                    x: (2 * self.quadrant_size) + rel_pos.x,
-                    y: 0,
-                },
-                Orient::Down,
-            ),
-            ((2, 0), Edge::Top) => (
-                Pos {
-                    x: rel_pos.x,
-                    y: (4 * self.quadrant_size) - 1,
-                },
-                Orient::Top,
-            ),
-            ((1, 2), Edge::Right) => (
-                Pos {
-                    x: (3 * self.quadrant_size) - 1,
-                    y: (1 * self.quadrant_size) - (rel_pos.y + 1),
-                },
-                Orient::Left,
-            ),
-            ((2, 0), Edge::Right) => (
-                Pos {
-                    x: (2 * self.quadrant_size) - 1,
-                    y: (3 * self.quadrant_size) - (rel_pos.y + 1),
-                },
-                Orient::Left,
-            ),
-            ((2, 0), Edge::Down) => (
-                Pos {
-                    x: (2 * self.quadrant_size) - 1,
-                    y: (1 * self.quadrant_size) + rel_pos.x,
-                },
-                Orient::Left,
-            ),
-            ((1, 1), Edge::Right) => (
-                Pos {
-                    x: (2 * self.quadrant_size) + rel_pos.y,
-                    y: (1 * self.quadrant_size) - 1,
-                },
-                Orient::Top,
-            ),
-            ((1, 1), Edge::Left) => (
-                Pos {
-                    x: rel_pos.y,
-                    y: (2 * self.quadrant_size),
-                },
-                Orient::Down,
-            ),
-            ((1, 0), Edge::Left) => (
-                Pos {
-                    x: 0,
-                    y: (3 * self.quadrant_size) - (rel_pos.y + 1),
-                },
-                Orient::Right,
-            ),
-            _ => {
-                //println!("quad={:?}; rel_pos={:?}; edge={:?}", quad, rel_pos, edge);
-                unimplemented!()
-            }
-        }
-    }
-
-    fn get_current_quadrant_and_relative_pos(&self) -> (Quadrant, RelativePos) {
+    fn quadrant_and_relative_pos(&self, pos: Pos) -> (Quadrant, RelativePos) {
         let quad = Quadrant {
-            y: self.pos.y / self.quadrant_size as i64,
-            x: self.pos.x / self.quadrant_size as i64,
+            y: pos.y / self.quadrant_size,
+            x: pos.x / self.quadrant_size,
         };
         let rel_pos = RelativePos {
-            y: self.pos.y % self.quadrant_size as i64,
-            x: self.pos.x % self.quadrant_size as i64,
+            y: pos.y % self.quadrant_size,
+            x: pos.x % self.quadrant_size,
         };
         (quad, rel_pos)
     }
 
-    fn get_current_quadrant_edge(&self, pos: RelativePos) -> Option<Edge> {
-        match self.orient {
-            Orient::Top if pos.y == 0 => Some(self.orient),
-            Orient::Right if pos.x == self.quadrant_size - 1 => Some(self.orient),
-            Orient::Down if pos.y == self.quadrant_size - 1 => Some(self.orient),
-            Orient::Left if pos.x == 0 => Some(self.orient),
-            _ => None,
+    // clockwise position of `rel_pos` along `edge`
+    fn edge_offset(&self, edge: Edge, rel_pos: RelativePos) -> i64 {
+        let last = self.quadrant_size - 1;
+        match edge {
+            Edge::Top => rel_pos.x,
+            Edge::Right => rel_pos.y,
+            Edge::Down => last - rel_pos.x,
+            Edge::Left => last - rel_pos.y,
         }
     }
 
-    fn print_map(&self) {
-        let x_max = self.map.keys().map(|pos| pos.x).max().unwrap() as usize;
-        let y_max = self.map.keys().map(|pos| pos.y).max().unwrap() as usize;
-        let mut m = Matrix::from_iter(y_max + 1, x_max + 1, iter::repeat(' '));
-        for (pos, elem) in self.map.iter() {
-            match elem {
-                Elem::Wall => m.set(pos.y as usize, pos.x as usize, '#'),
-                Elem::Plain => m.set(pos.y as usize, pos.x as usize, '.'),
-            };
+    fn edge_pos(&self, quad: Quadrant, edge: Edge, offset: i64) -> Pos {
+        let last = self.quadrant_size - 1;
+        let rel_pos = match edge {
+            Edge::Top => RelativePos { x: offset, y: 0 },
+            Edge::Right => RelativePos { x: last, y: offset },
+            Edge::Down => RelativePos {
+                x: last - offset,
+                y: last,
+            },
+            Edge::Left => RelativePos {
+                x: 0,
+                y: last - offset,
+            },
+        };
+        Pos {
+            x: quad.x * self.quadrant_size + rel_pos.x,
+            y: quad.y * self.quadrant_size + rel_pos.y,
         }
-        m.set(
-            self.pos.y as usize,
-            self.pos.x as usize,
-            self.orient.to_char(),
-        );
-
-        print_matrix(&m);
-        println!();
     }
 }
 
-#[derive(Debug)]
-enum Instr {
-    Steps(usize),
-    Rotate(Orient),
-}
+impl WrapRule for Cube {
+    fn wrap(&self, map: &Board, pos: Pos, orient: Orient) -> Option<(Pos, Orient)> {
+        let (quad, rel_pos) = self.quadrant_and_relative_pos(pos);
+        let edge = orient;
 
-type Instrs = VecDeque<Instr>;
+        trace!("quad={:?}; rel_pos={:?}; edge={:?}", quad, rel_pos, edge);
 
-fn get_instrs(loader: &DataLoader) -> Instrs {
-    let mut res = VecDeque::new();
-    let mut data = vec![];
+        let (next_quad, next_edge) = self.gluing[&(quad, edge)];
+        // walking an edge clockwise on one face walks it counter-clockwise on the other
+        let offset = self.quadrant_size - 1 - self.edge_offset(edge, rel_pos);
+        Some((
+            self.edge_pos(next_quad, next_edge, offset),
+            next_edge.opposite(),
+        ))
+    }
+}
 
-    for c in loader.iter().last().unwrap().chars() {
-        if c.is_digit(10) {
-            data.push(c);
-        } else {
-            res.push_back(Instr::Steps(
-                String::from_iter(data.iter()).parse::<usize>().unwrap(),
-            ));
-            data.clear();
+pub fn solve_b(loader: &DataLoader) -> Result<String, &str> {
+    let map = board_from_loader(loader);
+    let cube = Cube::fold(&map)?;
+    let mut world = World::new(map, cube)?;
+    world.run(get_instrs(loader).iter());
 
-            res.push_back(Instr::Rotate(Orient::try_from(c).unwrap()));
-        }
-    }
+    Ok(world.password().to_string())
+}
 
-    res.push_back(Instr::Steps(
-        String::from_iter(data.iter()).parse::<usize>().unwrap(),
-    ));
+pub fn trace_b(loader: &DataLoader, per_face: bool) -> Result<String, &str> {
+    let map = board_from_loader(loader);
+    let cube = Cube::fold(&map)?;
+    let mut world = World::new(map, cube)?;
+    world.run(get_instrs(loader).iter());
 
-    res
+    match per_face {
+        true => Ok(world.render_trail_faces(world.rule().quadrant_size())),
+        false => Ok(world.render_trail()),
+    }
 }
 
-pub fn solve_b(loader: &DataLoader, quadrant_size: i64) -> Result<String, &str> {
-    let mut world = World::from_loader(loader, quadrant_size);
-    let instrs = get_instrs(loader);
+#[cfg(test)]
+mod test_main {
+    use super::*;
 
-    for (i, instr) in instrs.iter().enumerate() {
-        match instr {
-            Instr::Steps(steps) => world.go(*steps),
-            Instr::Rotate(to) => world.rotate(*to),
+    fn transposed(loader: &DataLoader) -> DataLoader {
+        let lines = loader
+            .iter()
+            .take_while(|line| !line.is_empty())
+            .map(|line| line.chars().collect::<Vec<char>>())
+            .collect::<Vec<Vec<char>>>();
+        let width = lines.iter().map(|line| line.len()).max().unwrap();
+
+        let mut res = (0..width)
+            .map(|x| {
+                let line = lines.iter().map(|line| *line.get(x).unwrap_or(&' '));
+                String::from_iter(line).trim_end().to_string()
+            })
+            .collect::<Vec<String>>();
+        res.push(String::new());
+        res.push(loader.iter().last().unwrap().clone());
+        DataLoader::from_data(&res)
+    }
+
+    fn assert_wraps_back(loader: &DataLoader) {
+        let map = board_from_loader(loader);
+        let cube = Cube::fold(&map).unwrap();
+        let edge_cells = map
+            .keys()
+            .flat_map(|pos| ORIENTS.map(|orient| (*pos, orient)))
+            .filter(|(pos, orient)| !map.contains_key(&pos.next(*orient)))
+            .collect::<Vec<(Pos, Orient)>>();
+        assert_eq!(edge_cells.len() as i64, 14 * cube.quadrant_size);
+
+        for (pos, orient) in edge_cells {
+            let (wrap_pos, wrap_orient) = cube.wrap(&map, pos, orient).unwrap();
+            assert_eq!(
+                cube.wrap(&map, wrap_pos, wrap_orient.opposite()),
+                Some((pos, orient.opposite()))
+            );
         }
     }
 
-    let res = (world.pos.y + 1) * 1000 + (world.pos.x + 1) * 4 + world.orient as i64;
-    Ok(res.to_string())
+    #[test]
+    fn fold_test() {
+        for file in ["data/test_input.txt", "data/puzzle_input.txt"] {
+            let loader = DataLoader::from_file(file);
+            assert_wraps_back(&loader);
+            assert_wraps_back(&transposed(&loader));
+        }
+
+        let loader =
+            DataLoader::from_data(&vec!["....".to_string(), String::new(), "1".to_string()]);
+        assert_eq!(
+            Cube::fold(&board_from_loader(&loader)).unwrap_err(),
+            "map is not a cube net"
+        );
+    }
 }
@@ -0,0 +1,363 @@
+use aoc_helpers::data_loader::DataLoader;
+use aoc_helpers::render::{Layer, Render, Renderer, Viewport};
+use itertools::{unfold, Itertools};
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use tracing::trace;
+
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub enum Orient {
+    Right = 0,
+    Down = 1,
+    Left = 2,
+    Top = 3,
+}
+
+impl Orient {
+    fn try_from(c: char) -> Option<Orient> {
+        match c {
+            'L' => Some(Orient::Left),
+            'R' => Some(Orient::Right),
+            _ => None,
+        }
+    }
+
+    pub fn opposite(&self) -> Orient {
+        match self {
+            Orient::Top => Orient::Down,
+            Orient::Down => Orient::Top,
+            Orient::Left => Orient::Right,
+            Orient::Right => Orient::Left,
+        }
+    }
+
+    fn rotate(&self, orient: Orient) -> Orient {
+        match orient {
+            Orient::Right => match self {
+                Orient::Top => Orient::Right,
+                Orient::Right => Orient::Down,
+                Orient::Down => Orient::Left,
+                Orient::Left => Orient::Top,
+            },
+            Orient::Left => self
+                .rotate(Orient::Right)
+                .rotate(Orient::Right)
+                .rotate(Orient::Right),
+            _ => panic!("rotation to top or down not possible"),
+        }
+    }
+
+    fn to_char(self) -> char {
+        match self {
+            Orient::Top => '^',
+            Orient::Right => '>',
+            Orient::Left => '<',
+            Orient::Down => 'v',
+        }
+    }
+}
+
+pub const ORIENTS: [Orient; 4] = [Orient::Right, Orient::Down, Orient::Left, Orient::Top];
+
+#[derive(Debug, Clone, Copy)]
+pub enum Elem {
+    Wall,
+    Plain,
+}
+
+impl Elem {
+    fn try_from(c: char) -> Option<Elem> {
+        match c {
+            '.' => Some(Elem::Plain),
+            '#' => Some(Elem::Wall),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Pos {
+    pub y: i64,
+    pub x: i64,
+}
+
+impl Pos {
+    pub fn next(&self, orient: Orient) -> Pos {
+        let mut res = *self;
+        match orient {
+            Orient::Right => res.x += 1,
+            Orient::Down => res.y += 1,
+            Orient::Left => res.x -= 1,
+            Orient::Top => res.y -= 1,
+        }
+        res
+    }
+}
+
+pub type Board = HashMap<Pos, Elem>;
+
+pub fn board_from_loader(loader: &DataLoader) -> Board {
+    let mut map = HashMap::new();
+    loader
+        .iter()
+        .take_while(|line| !line.is_empty())
+        .enumerate()
+        .for_each(|(row, line)| {
+            line.chars()
+                .enumerate()
+                .filter_map(|(col, c)| Elem::try_from(c).map(|elem| (col as i64, elem)))
+                .for_each(|(col, elem)| {
+                    map.insert(
+                        Pos {
+                            x: col,
+                            y: row as i64,
+                        },
+                        elem,
+                    );
+                })
+        });
+    map
+}
+
+// where to continue when walking off the board at `pos` facing `orient`
+pub trait WrapRule {
+    fn wrap(&self, map: &Board, pos: Pos, orient: Orient) -> Option<(Pos, Orient)>;
+}
+
+// part A: continue on the other side of the current row or column
+#[derive(Debug, Clone, Copy)]
+pub struct Torus;
+
+impl WrapRule for Torus {
+    fn wrap(&self, map: &Board, pos: Pos, orient: Orient) -> Option<(Pos, Orient)> {
+        let res = unfold(pos, |p| {
+            *p = p.next(orient.opposite());
+            Some(*p)
+        })
+        .take_while(|p| map.get(p).is_some())
+        .last()
+        .unwrap_or(pos);
+        Some((res, orient))
+    }
+}
+
+// explicit jumps, walking off the board anywhere else is blocked
+#[derive(Debug, Clone, Default)]
+pub struct Portals(pub HashMap<(Pos, Orient), (Pos, Orient)>);
+
+impl WrapRule for Portals {
+    fn wrap(&self, map: &Board, pos: Pos, orient: Orient) -> Option<(Pos, Orient)> {
+        self.0.get(&(pos, orient)).copied()
+    }
+}
+
+#[derive(Debug)]
+pub enum Instr {
+    Steps(usize),
+    Rotate(Orient),
+}
+
+pub type Instrs = VecDeque<Instr>;
+
+pub fn get_instrs(loader: &DataLoader) -> Instrs {
+    let mut res = VecDeque::new();
+    let mut data = vec![];
+
+    for c in loader.iter().last().unwrap().chars() {
+        if c.is_ascii_digit() {
+            data.push(c);
+        } else {
+            res.push_back(Instr::Steps(
+                String::from_iter(data.iter()).parse::<usize>().unwrap(),
+            ));
+            data.clear();
+
+            res.push_back(Instr::Rotate(Orient::try_from(c).unwrap()));
+        }
+    }
+
+    res.push_back(Instr::Steps(
+        String::from_iter(data.iter()).parse::<usize>().unwrap(),
+    ));
+
+    res
+}
+
+#[derive(Debug)]
+pub struct World<R: WrapRule> {
+    pub map: Board,
+    pub pos: Pos,
+    pub orient: Orient,
+    pub trail: Vec<(Pos, Orient)>,
+    rule: R,
+}
+
+impl<R: WrapRule> World<R> {
+    pub fn new(map: Board, rule: R) -> Result<World<R>, &'static str> {
+        let pos = *map.keys().min().ok_or("empty map")?;
+
+        Ok(World {
+            map,
+            pos,
+            orient: Orient::Right,
+            trail: vec![(pos, Orient::Right)],
+            rule,
+        })
+    }
+
+    pub fn go(&mut self, steps: usize) {
+        if steps == 0 {
+            return;
+        }
+
+        let next_pos = self.pos.next(self.orient);
+        match self.map.get(&next_pos) {
+            None => {
+                self.trace_map();
+                match self.rule.wrap(&self.map, self.pos, self.orient) {
+                    None => return,
+                    Some((wrap_pos, wrap_orient)) => match self.map.get(&wrap_pos) {
+                        None => panic!("should never happen"),
+                        Some(Elem::Wall) => {
+                            trace!("wall behind wrap around");
+                            return;
+                        }
+                        Some(Elem::Plain) => {
+                            self.pos = wrap_pos;
+                            self.orient = wrap_orient;
+                            self.trail.push((self.pos, self.orient));
+                            self.trace_map();
+                        }
+                    },
+                }
+            }
+            Some(Elem::Wall) => return,
+            Some(Elem::Plain) => {
+                self.pos = next_pos;
+                self.trail.push((self.pos, self.orient));
+            }
+        }
+
+        self.go(steps - 1);
+    }
+
+    pub fn rotate(&mut self, direction: Orient) {
+        self.orient = self.orient.rotate(direction);
+        if let Some(last) = self.trail.last_mut() {
+            last.1 = self.orient;
+        }
+    }
+
+    pub fn rule(&self) -> &R {
+        &self.rule
+    }
+
+    pub fn run<'a, I: IntoIterator<Item = &'a Instr>>(&mut self, instrs: I) {
+        for instr in instrs {
+            match instr {
+                Instr::Steps(steps) => self.go(*steps),
+                Instr::Rotate(to) => self.rotate(*to),
+            }
+        }
+    }
+
+    pub fn password(&self) -> i64 {
+        (self.pos.y + 1) * 1000 + (self.pos.x + 1) * 4 + self.orient as i64
+    }
+
+    pub fn render_position(&self) -> String {
+        let mut pos_layer = Layer::new();
+        pos_layer.set(self.pos.x, self.pos.y, self.orient.to_char());
+
+        Renderer::new().layer(pos_layer).render(self)
+    }
+
+    fn trail_layer(&self) -> Layer {
+        Layer::from_iter(
+            self.trail
+                .iter()
+                .map(|(pos, orient)| ((pos.x, pos.y), orient.to_char())),
+        )
+    }
+
+    pub fn render_trail(&self) -> String {
+        Renderer::new().layer(self.trail_layer()).render(self)
+    }
+
+    pub fn render_trail_faces(&self, size: i64) -> String {
+        let faces = self
+            .map
+            .keys()
+            .filter(|pos| pos.x % size == 0 && pos.y % size == 0)
+            .sorted();
+
+        faces
+            .enumerate()
+            .map(|(i, corner)| {
+                let viewport =
+                    Viewport::new(corner.x, corner.y, corner.x + size - 1, corner.y + size - 1);
+                let face = Renderer::new()
+                    .viewport(viewport)
+                    .layer(self.trail_layer())
+                    .render(self);
+                format!(
+                    "face {} at ({}, {}):\n{face}",
+                    i + 1,
+                    corner.x / size,
+                    corner.y / size
+                )
+            })
+            .join("\n")
+    }
+
+    fn trace_map(&self) {
+        trace!("\n{}", self.render_position());
+    }
+}
+
+impl<R: WrapRule> Render for World<R> {
+    fn viewport(&self) -> Option<Viewport> {
+        Viewport::from_points(self.map.keys().map(|pos| (pos.x, pos.y)))
+            .map(|vp| Viewport::new(0, 0, vp.x_max, vp.y_max))
+    }
+
+    fn cell(&self, x: i64, y: i64) -> char {
+        match self.map.get(&Pos { x, y }) {
+            Some(Elem::Wall) => '#',
+            Some(Elem::Plain) => '.',
+            None => ' ',
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_main {
+    use super::*;
+
+    #[test]
+    fn portals_test() {
+        let loader = DataLoader::from_file("data/test_input.txt");
+        let map = board_from_loader(&loader);
+        let instrs = get_instrs(&loader);
+
+        // portals replicating the torus wrap behave exactly like it
+        let portals = map
+            .keys()
+            .flat_map(|pos| ORIENTS.map(|orient| (*pos, orient)))
+            .filter(|(pos, orient)| !map.contains_key(&pos.next(*orient)))
+            .map(|(pos, orient)| ((pos, orient), Torus.wrap(&map, pos, orient).unwrap()))
+            .collect();
+        let mut torus = World::new(map.clone(), Torus).unwrap();
+        let mut portals = World::new(map.clone(), Portals(portals)).unwrap();
+        torus.run(instrs.iter());
+        portals.run(instrs.iter());
+        assert_eq!(portals.password(), torus.password());
+
+        // without any portals the board edge blocks like a wall
+        let mut closed = World::new(map, Portals::default()).unwrap();
+        closed.run([Instr::Steps(10)].iter());
+        assert_eq!(closed.pos, Pos { x: 10, y: 0 });
+        closed.run([Instr::Rotate(Orient::Left), Instr::Steps(3)].iter());
+        assert_eq!(closed.pos, Pos { x: 10, y: 0 });
+    }
+}
@@ -2,19 +2,28 @@
 #![allow(unused_variables, dead_code)]
 
 use aoc_helpers::data_loader::DataLoader;
+use aoc_helpers::logging;
 
 mod solver;
-use crate::solver::{solve_a, solve_b};
+use crate::solver::{animate_a, solve_a, solve_b};
 
 fn main() {
+    logging::init();
+
     let a_input = DataLoader::from_file("data/puzzle_input.txt");
     println!("a: {}", solve_a(&a_input).unwrap());
     println!("b: {}", solve_b(&a_input).unwrap());
+
+    if let Some(path) = logging::positional_args().first() {
+        animate_a(&a_input, 10).unwrap().save(path).unwrap();
+    }
 }
 
 #[cfg(test)]
 mod test_main {
     use super::*;
+    use crate::solver::render_a;
+    use aoc_helpers::snapshot::assert_snapshot;
 
     #[test]
     fn a_test() {
@@ -31,4 +40,27 @@ mod test_main {
             DataLoader::from_file("data/b_test_result.txt").test_result()
         );
     }
+
+    #[test]
+    fn a_snapshot_test() {
+        let loader = DataLoader::from_file("data/test_input.txt");
+        for rounds in [0, 1, 5, 10] {
+            assert_snapshot(
+                &render_a(&loader, rounds).unwrap(),
+                &format!("data/a_test_snapshot_{rounds}.txt"),
+            );
+        }
+    }
+
+    #[test]
+    fn a_animation_test() {
+        let animation = animate_a(&DataLoader::from_file("data/test_input.txt"), 10).unwrap();
+        assert_eq!(animation.frames().len(), 11);
+        for rounds in [0, 1, 5, 10] {
+            assert_snapshot(
+                &animation.frames()[rounds],
+                &format!("data/a_test_snapshot_{rounds}.txt"),
+            );
+        }
+    }
 }
@@ -1,12 +1,12 @@
+use aoc_helpers::animation::Animation;
 use aoc_helpers::data_loader::DataLoader;
-use aoc_helpers::matrix_helper::{get_flatten_matrix, print_matrix, MatrixLoader};
-use aoc_helpers::slide_iter::SlideIterator;
-use itertools::{unfold, Itertools};
-use simple_matrix::Matrix;
-use std::collections::HashSet;
+use aoc_helpers::matrix_helper::{get_flatten_matrix, MatrixLoader};
+use aoc_helpers::render::{Layer, Render, Renderer};
+use itertools::Itertools;
+use std::collections::HashMap;
 use std::collections::VecDeque;
-use std::iter;
 use std::ops::Add;
+use tracing::{debug, trace};
 
 #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
 struct Pos {
@@ -26,7 +26,7 @@ impl Add for Pos {
 }
 
 #[derive(Debug, Clone, Copy)]
-enum Direct {
+pub enum Direct {
     N,
     NW,
     NE,
@@ -52,47 +52,105 @@ impl Direct {
     }
 }
 
-type Grid = HashSet<Pos>;
-type Directs = VecDeque<Direct>;
+// dense occupancy over a bounded area which grows when an elf leaves it
+#[derive(Debug, Clone)]
+struct Grid {
+    min: Pos,
+    width: i64,
+    height: i64,
+    cells: Vec<bool>,
+    elves: Vec<Pos>,
+}
 
-fn get_grid(loader: &DataLoader) -> Grid {
-    let mut res = HashSet::new();
+impl Grid {
+    fn new(elves: Vec<Pos>) -> Grid {
+        let mut res = Grid {
+            min: Pos { x: 0, y: 0 },
+            width: 0,
+            height: 0,
+            cells: vec![],
+            elves,
+        };
+        res.resize();
+        res
+    }
+
+    fn idx(&self, pos: &Pos) -> Option<usize> {
+        let (x, y) = (pos.x - self.min.x, pos.y - self.min.y);
+        if (0..self.width).contains(&x) && (0..self.height).contains(&y) {
+            Some((y * self.width + x) as usize)
+        } else {
+            None
+        }
+    }
+
+    fn contains(&self, pos: &Pos) -> bool {
+        self.idx(pos).is_some_and(|idx| self.cells[idx])
+    }
+
+    fn iter(&self) -> std::slice::Iter<'_, Pos> {
+        self.elves.iter()
+    }
 
-    get_flatten_matrix(&loader.to_matrix::<char>().unwrap())
+    fn len(&self) -> usize {
+        self.elves.len()
+    }
+
+    fn resize(&mut self) {
+        let (min_x, max_x) = self
+            .iter()
+            .map(|pos| pos.x)
+            .minmax()
+            .into_option()
+            .unwrap_or((0, 0));
+        let (min_y, max_y) = self
+            .iter()
+            .map(|pos| pos.y)
+            .minmax()
+            .into_option()
+            .unwrap_or((0, 0));
+        let margin = 1 + (max_x - min_x).max(max_y - min_y) / 2;
+
+        self.min = Pos {
+            x: min_x - margin,
+            y: min_y - margin,
+        };
+        self.width = 1 + max_x - min_x + 2 * margin;
+        self.height = 1 + max_y - min_y + 2 * margin;
+        self.cells = vec![false; (self.width * self.height) as usize];
+        for i in 0..self.elves.len() {
+            let idx = self.idx(&self.elves[i]).unwrap();
+            self.cells[idx] = true;
+        }
+    }
+
+    fn move_elf(&mut self, elf: usize, to: Pos) {
+        let from = self.idx(&self.elves[elf]).unwrap();
+        self.cells[from] = false;
+        self.elves[elf] = to;
+
+        match self.idx(&to) {
+            Some(idx) => self.cells[idx] = true,
+            None => self.resize(),
+        }
+    }
+}
+
+fn get_grid(loader: &DataLoader) -> Grid {
+    let elves = get_flatten_matrix(&loader.to_matrix::<char>().unwrap())
         .iter()
         .filter(|(_, _, c)| **c == '#')
-        .for_each(|(y, x, _)| {
-            res.insert(Pos {
-                x: (*x as i64),
-                y: (*y as i64),
-            });
-        });
+        .map(|(y, x, _)| Pos {
+            x: (*x as i64),
+            y: (*y as i64),
+        })
+        .collect();
 
-    res
+    Grid::new(elves)
 }
 
-fn print_grid(grid: &Grid) {
-    let (min_x, max_x) = grid.iter().map(|pos| pos.x).minmax().into_option().unwrap();
-    let (min_y, max_y) = grid.iter().map(|pos| pos.y).minmax().into_option().unwrap();
-    let x_offset = min_x;
-    let y_offset = min_y;
-
-    let mut mat = Matrix::from_iter(
-        (1 + max_y - min_y) as usize,
-        (1 + max_x - min_x) as usize,
-        iter::repeat('.'),
-    );
-
-    for pos in grid.iter() {
-        mat.set(
-            (pos.y - y_offset) as usize,
-            (pos.x - x_offset) as usize,
-            '#',
-        );
-    }
-
-    print_matrix(&mat);
-    println!("");
+fn trace_grid(grid: &Grid) {
+    trace!("\n{}", grid_layer(grid).render());
 }
 
 fn is_clear(pos: &Pos, to_check: &[Pos], grid: &Grid) -> bool {
@@ -102,65 +160,92 @@ fn is_clear(pos: &Pos, to_check: &[Pos], grid: &Grid) -> bool {
         .all(|p| !grid.contains(&p))
 }
 
-fn calc_next_round(grid: &Grid, directs: &Directs) -> Grid {
-    let mut res = Grid::new();
-    let mut proposed_moves: Vec<(Pos, Pos)> = Vec::new();
+// an elf steps towards `step` when none of the `blockers` is occupied
+#[derive(Debug, Clone)]
+pub struct Rule {
+    step: Direct,
+    blockers: Vec<Pos>,
+}
 
-    for pos in grid.iter() {
-        if is_clear(pos, &get_all_pos(), grid) {
-            proposed_moves.push((*pos, *pos));
-        } else {
-            if let Some(direct) = directs.iter().find_map(|d| {
-                if is_clear(pos, &get_search_poses(*d), grid) {
-                    Some(d)
-                } else {
-                    None
-                }
-            }) {
-                proposed_moves.push((*pos, (*pos + direct.pos())));
-            } else {
-                proposed_moves.push((*pos, *pos));
-            }
+impl Rule {
+    pub fn new(step: Direct, blockers: &[Direct]) -> Rule {
+        Rule {
+            step,
+            blockers: blockers.iter().map(|d| d.pos()).collect(),
         }
     }
+}
 
-    for (pos, new_pos) in proposed_moves.iter() {
-        if 1 < proposed_moves
-            .iter()
-            .filter(|(_, n)| n == new_pos)
-            .take(2)
-            .count()
-        {
-            res.insert(*pos);
-        } else {
-            res.insert(*new_pos);
+#[derive(Debug, Clone)]
+pub struct RuleSet {
+    // an elf with all of these neighbours empty stays where it is
+    idle: Vec<Pos>,
+    // checked in order, the first rule moves to the back after each round
+    rules: VecDeque<Rule>,
+}
+
+impl RuleSet {
+    pub fn new(idle: &[Direct], rules: Vec<Rule>) -> RuleSet {
+        RuleSet {
+            idle: idle.iter().map(|d| d.pos()).collect(),
+            rules: rules.into(),
         }
     }
 
-    res
+    pub fn standard() -> RuleSet {
+        use Direct::*;
+
+        RuleSet::new(
+            &[N, NE, E, SE, S, SW, W, NW],
+            vec![
+                Rule::new(N, &[N, NW, NE]),
+                Rule::new(S, &[S, SE, SW]),
+                Rule::new(W, &[W, NW, SW]),
+                Rule::new(E, &[E, NE, SE]),
+            ],
+        )
+    }
+
+    fn rotate(&mut self) {
+        self.rules.rotate_left(1);
+    }
 }
 
-fn get_all_pos() -> [Pos; 8] {
-    [
-        Direct::N.pos(),
-        Direct::NE.pos(),
-        Direct::E.pos(),
-        Direct::SE.pos(),
-        Direct::S.pos(),
-        Direct::SW.pos(),
-        Direct::W.pos(),
-        Direct::NW.pos(),
-    ]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stabilization {
+    pub first_still_round: usize,
+    pub moves: Vec<usize>,
 }
 
-fn get_search_poses(direct: Direct) -> [Pos; 3] {
-    match direct {
-        Direct::N => [Direct::N.pos(), Direct::NW.pos(), Direct::NE.pos()],
-        Direct::S => [Direct::S.pos(), Direct::SE.pos(), Direct::SW.pos()],
-        Direct::W => [Direct::W.pos(), Direct::NW.pos(), Direct::SW.pos()],
-        Direct::E => [Direct::E.pos(), Direct::NE.pos(), Direct::SE.pos()],
-        _ => panic!("should not happen"),
+// moves the elves in place and returns how many of them moved
+fn calc_next_round(grid: &mut Grid, rules: &RuleSet) -> usize {
+    let mut proposals: Vec<(usize, Pos)> = Vec::new();
+    let mut targets: HashMap<Pos, usize> = HashMap::new();
+
+    for (elf, pos) in grid.iter().enumerate() {
+        if is_clear(pos, &rules.idle, grid) {
+            continue;
+        }
+        if let Some(rule) = rules
+            .rules
+            .iter()
+            .find(|rule| is_clear(pos, &rule.blockers, grid))
+        {
+            let target = *pos + rule.step.pos();
+            proposals.push((elf, target));
+            *targets.entry(target).or_default() += 1;
+        }
+    }
+
+    let mut moved = 0;
+    for (elf, target) in proposals {
+        if targets[&target] == 1 {
+            grid.move_elf(elf, target);
+            moved += 1;
+        }
     }
+
+    moved
 }
 
 fn calc_result(grid: &Grid) -> usize {
@@ -172,43 +257,116 @@ fn calc_result(grid: &Grid) -> usize {
     (grid_size as usize) - grid.len()
 }
 
+fn grid_layer(grid: &Grid) -> Layer {
+    Layer::from_iter(grid.iter().map(|pos| ((pos.x, pos.y), '#')))
+}
+
+fn run_rounds<F: FnMut(&Grid)>(grid: Grid, count: usize, mut on_round: F) -> Grid {
+    let mut rules = RuleSet::standard();
+    let mut grid = grid;
+
+    for _ in 0..count {
+        calc_next_round(&mut grid, &rules);
+        rules.rotate();
+        on_round(&grid);
+    }
+
+    grid
+}
+
 pub fn solve_a(loader: &DataLoader) -> Result<String, &str> {
     let grid = get_grid(loader);
-    let directs: Directs = [Direct::N, Direct::S, Direct::W, Direct::E].into();
-
-    println!("loader");
-    print_grid(&grid);
-
-    let rounds = unfold((grid, directs), |(g, d)| {
-        *g = calc_next_round(g, d);
-        let tmp = d.pop_front().unwrap();
-        d.push_back(tmp);
-        Some(g.clone())
-    });
-    let res = calc_result(&rounds.take(10).last().unwrap());
+
+    trace_grid(&grid);
+
+    let res = calc_result(&run_rounds(grid, 10, |_| ()));
     Ok(res.to_string())
 }
 
-pub fn solve_b(loader: &DataLoader) -> Result<String, &str> {
+pub fn render_a(loader: &DataLoader, round_count: usize) -> Result<String, &str> {
+    let grid = run_rounds(get_grid(loader), round_count, |_| ());
+    Ok(grid_layer(&grid).render())
+}
+
+pub fn animate_a(loader: &DataLoader, round_count: usize) -> Result<Animation, &str> {
     let grid = get_grid(loader);
-    let directs: Directs = [Direct::N, Direct::S, Direct::W, Direct::E].into();
-
-    println!("loader");
-    print_grid(&grid);
-
-    let rounds = unfold((grid, directs), |(g, d)| {
-        *g = calc_next_round(g, d);
-        let tmp = d.pop_front().unwrap();
-        d.push_back(tmp);
-        Some(g.clone())
-    });
-    let last_round = rounds.enumerate().slide(2).find_map(|v| {
-        println!("round={}", v[1].0);
-        if v[0].1 == v[1].1 {
-            Some(v[1].0 + 1)
-        } else {
-            None
+    let mut animation = Animation::new(Renderer::new());
+
+    animation.push(&grid_layer(&grid));
+    run_rounds(grid, round_count, |g| animation.push(&grid_layer(g)));
+
+    Ok(animation)
+}
+
+pub fn run_until_stable(
+    loader: &DataLoader,
+    rules: &RuleSet,
+    max_rounds: usize,
+) -> Result<Stabilization, &'static str> {
+    let mut grid = get_grid(loader);
+    let mut rules = rules.clone();
+    let mut moves = vec![];
+
+    trace_grid(&grid);
+
+    while moves.len() < max_rounds {
+        let moved = calc_next_round(&mut grid, &rules);
+        debug!("round={} moved={}", moves.len() + 1, moved);
+        moves.push(moved);
+        if moved == 0 {
+            return Ok(Stabilization {
+                first_still_round: moves.len(),
+                moves,
+            });
         }
-    }).unwrap();
-    Ok(last_round.to_string())
+        rules.rotate();
+    }
+    Err("elves did not settle")
+}
+
+pub fn solve_b(loader: &DataLoader) -> Result<String, &str> {
+    let res = run_until_stable(loader, &RuleSet::standard(), 100_000)?;
+    Ok(res.first_still_round.to_string())
+}
+
+#[cfg(test)]
+mod test_main {
+    use super::*;
+
+    #[test]
+    fn run_until_stable_test() {
+        let loader = DataLoader::from_file("data/test_input.txt");
+        let res = run_until_stable(&loader, &RuleSet::standard(), 100).unwrap();
+        assert_eq!(res.first_still_round, 20);
+        assert_eq!(res.moves.len(), 20);
+        assert_eq!(res.moves.last(), Some(&0));
+        assert!(res.moves[..19].iter().all(|moved| *moved > 0));
+        assert_eq!(
+            run_until_stable(&loader, &RuleSet::standard(), 10),
+            Err("elves did not settle")
+        );
+    }
+
+    #[test]
+    fn rule_variants_test() {
+        use Direct::*;
+
+        let loader = DataLoader::from_data(&vec!["##".to_string(), "..".to_string()]);
+        let idle = [N, NE, E, SE, S, SW, W, NW];
+
+        let east_only = RuleSet::new(&idle, vec![Rule::new(E, &[E, NE, SE])]);
+        assert_eq!(
+            run_until_stable(&loader, &east_only, 10),
+            Ok(Stabilization {
+                first_still_round: 2,
+                moves: vec![1, 0],
+            })
+        );
+
+        let north_only = RuleSet::new(&idle, vec![Rule::new(N, &[N, NW, NE])]);
+        assert_eq!(
+            run_until_stable(&loader, &north_only, 10),
+            Err("elves did not settle")
+        );
+    }
 }
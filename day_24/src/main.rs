@@ -2,19 +2,27 @@
 #![allow(unused_variables, dead_code)]
 
 use aoc_helpers::data_loader::DataLoader;
+use aoc_helpers::logging;
 
 mod solver;
-use crate::solver::{solve_a, solve_b};
+use crate::solver::{animate, solve_a, solve_b};
 
 fn main() {
+    logging::init();
+
     let a_input = DataLoader::from_file("data/puzzle_input.txt");
     println!("a: {}", solve_a(&a_input).unwrap());
     println!("b: {}", solve_b(&a_input).unwrap());
+
+    if let Some(path) = logging::positional_args().first() {
+        animate(&a_input, 600).unwrap().save(path).unwrap();
+    }
 }
 
 #[cfg(test)]
 mod test_main {
     use super::*;
+    use aoc_helpers::snapshot::assert_snapshot;
 
     #[test]
     fn a_test() {
@@ -31,4 +39,17 @@ mod test_main {
             DataLoader::from_file("data/b_test_result.txt").test_result()
         );
     }
+
+    #[test]
+    fn animation_test() {
+        let animation = animate(&DataLoader::from_file("data/test_input.txt"), 12).unwrap();
+        assert_eq!(animation.frames().len(), 13);
+        for round in [1, 5, 12] {
+            assert_snapshot(
+                &animation.frames()[round],
+                &format!("data/test_snapshot_{round}.txt"),
+            );
+        }
+        assert_eq!(animation.frames()[0], animation.frames()[12]);
+    }
 }
@@ -1,12 +1,16 @@
+use aoc_helpers::animation::Animation;
 use aoc_helpers::data_loader::DataLoader;
+use aoc_helpers::render::{Render, Renderer, Viewport};
 use cond_utils::Between;
-use std::collections::{HashSet, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
 use std::ops::Add;
+use tracing::{debug, trace};
 
 #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
-struct Pos {
-    x: i64,
-    y: i64,
+pub struct Pos {
+    pub x: i64,
+    pub y: i64,
 }
 
 impl Add for Pos {
@@ -46,6 +50,15 @@ impl Direct {
             Direct::E => Direct::W,
         }
     }
+
+    fn to_char(self) -> char {
+        match self {
+            Direct::N => '^',
+            Direct::S => 'v',
+            Direct::E => '>',
+            Direct::W => '<',
+        }
+    }
 }
 
 fn get_all_poses() -> [Pos; 4] {
@@ -57,23 +70,91 @@ fn get_all_poses() -> [Pos; 4] {
     ]
 }
 
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Bits(Vec<u64>);
+
+impl Bits {
+    fn new(len: usize) -> Bits {
+        Bits(vec![0; len.div_ceil(64)])
+    }
+
+    fn set(&mut self, idx: usize) {
+        self.0[idx / 64] |= 1 << (idx % 64);
+    }
+
+    fn get(&self, idx: usize) -> bool {
+        self.0[idx / 64] & (1 << (idx % 64)) != 0
+    }
+}
+
+// blizzard occupancy at one point in time: horizontal blizzards per row,
+// vertical blizzards per column
+#[derive(Debug, Clone)]
+struct TimeSlice {
+    rows: Vec<Bits>,
+    cols: Vec<Bits>,
+}
+
 #[derive(Debug, Clone)]
 struct Grove {
     start: Pos,
-    start_direction: Direct,
     goal: Pos,
-    goal_direction: Direct,
     edge: Pos,
     h_blizz: Vec<Vec<(usize, Direct)>>,
     v_blizz: Vec<Vec<(usize, Direct)>>,
+    period: usize,
+    slices: Vec<TimeSlice>,
 }
 
 impl Grove {
+    fn new(
+        start: Pos,
+        goal: Pos,
+        edge: Pos,
+        h_blizz: Vec<Vec<(usize, Direct)>>,
+        v_blizz: Vec<Vec<(usize, Direct)>>,
+    ) -> Grove {
+        let (width, height) = (edge.x as usize, edge.y as usize - 1);
+        let mut res = Grove {
+            start,
+            goal,
+            edge,
+            h_blizz,
+            v_blizz,
+            period: width * height / gcd(width, height),
+            slices: vec![],
+        };
+
+        res.slices = (0..res.period)
+            .map(|round| {
+                // +2 covers the wall, start and goal rows and columns is_walkable indexes
+                let mut slice = TimeSlice {
+                    rows: vec![Bits::new(width + 2); height + 2],
+                    cols: vec![Bits::new(height + 2); width + 2],
+                };
+                for (pos, direct) in res.blizzards_at(round) {
+                    match direct {
+                        Direct::E | Direct::W => slice.rows[pos.y as usize].set(pos.x as usize),
+                        Direct::N | Direct::S => slice.cols[pos.x as usize].set(pos.y as usize),
+                    }
+                }
+                slice
+            })
+            .collect();
+        res
+    }
+
     fn from_loader(loader: &DataLoader) -> Grove {
         let start: Pos = Pos { x: 1, y: 0 };
-        let start_direction = Direct::S;
         let mut goal: Pos = Pos { x: -1, y: -1 };
-        let goal_direction = Direct::S;
         let mut h_blizz: Vec<Vec<(usize, Direct)>> = vec![];
         let mut v_blizz: Vec<Vec<(usize, Direct)>> = vec![];
 
@@ -110,144 +191,248 @@ impl Grove {
             }
         }
 
-        Grove {
-            start,
-            start_direction,
-            goal,
-            goal_direction,
-            edge: goal,
-            h_blizz,
-            v_blizz,
-        }
+        Grove::new(start, goal, goal, h_blizz, v_blizz)
     }
 
     fn is_walkable(&self, pos: Pos, round: usize) -> bool {
-        for (x, direct) in self.h_blizz[pos.y as usize].iter() {
-            let bliz_pos = match direct {
-                Direct::E => 1 + (x - 1 + round as usize) % (self.edge.x as usize),
-                Direct::W => 1 + (*x as i64 - 1 - round as i64).rem_euclid(self.edge.x) as usize,
-                _ => {
-                    println!("the impl derect={direct:?}");
-                    panic!("impossile direction")
-                }
-            };
-            if bliz_pos == (pos.x as usize) {
-                return false;
-            }
-        }
-
-        for (y, direct) in self.v_blizz[pos.x as usize].iter() {
-            let bliz_pos = match direct {
-                Direct::S => 1 + (y - 1 + round as usize) % (self.edge.y as usize - 1),
-                Direct::N => {
-                    1 + (*y as i64 - 1 - round as i64).rem_euclid(self.edge.y - 1) as usize
-                }
-                _ => {
-                    println!("the impl derect={direct:?}");
-                    panic!("impossile direction")
-                }
-            };
-            if bliz_pos == (pos.y as usize) {
-                return false;
-            }
-        }
+        let slice = &self.slices[round % self.period];
+        let (x, y) = (pos.x as usize, pos.y as usize);
+        !(slice.rows[y].get(x) || slice.cols[x].get(y))
+    }
 
-        true
+    fn is_open(&self, pos: Pos) -> bool {
+        pos == self.start
+            || pos == self.goal
+            || (pos.x.within(1, self.edge.x) && pos.y.within(1, self.edge.y - 1))
     }
 
     fn next_poses(&self, from_pos: Pos, round: usize) -> Vec<Pos> {
-        if from_pos == self.start {
-            let next_pos = self.start + self.start_direction.pos();
-            if self.is_walkable(next_pos, round) {
-                return vec![from_pos, next_pos];
-            } else {
-                return vec![from_pos];
-            }
-        }
+        get_all_poses()
+            .iter()
+            .map(|p| *p + from_pos)
+            .chain([from_pos])
+            .filter(|p| self.is_open(*p) && self.is_walkable(*p, round))
+            .collect()
+    }
 
-        if from_pos == (self.goal + self.goal_direction.opposite().pos()) {
-            return vec![self.goal];
+    fn waypoint(&self, waypoint: Waypoint) -> Pos {
+        match waypoint {
+            Waypoint::Start => self.start,
+            Waypoint::Goal => self.goal,
         }
+    }
 
-        let mut res = get_all_poses()
-            .iter()
-            .map(|p| *p + from_pos)
-            .filter(|p| p.x.within(1, self.edge.x) && p.y.within(1, self.edge.y - 1))
-            .filter(|p| self.is_walkable(*p, round))
-            .collect::<Vec<Pos>>();
+    // the only place blizzard movement is computed: the search slices and the
+    // rendered states are both built from it
+    fn blizzards_at(&self, round: usize) -> Vec<(Pos, Direct)> {
+        let h_blizz = self.h_blizz.iter().enumerate().flat_map(|(y, row)| {
+            row.iter().map(move |(x, direct)| {
+                let x = match direct {
+                    Direct::E => 1 + (*x as i64 - 1 + round as i64).rem_euclid(self.edge.x),
+                    _ => 1 + (*x as i64 - 1 - round as i64).rem_euclid(self.edge.x),
+                };
+                (Pos { x, y: y as i64 }, *direct)
+            })
+        });
+        let v_blizz = self.v_blizz.iter().enumerate().flat_map(|(x, col)| {
+            col.iter().map(move |(y, direct)| {
+                let y = match direct {
+                    Direct::S => 1 + (*y as i64 - 1 + round as i64).rem_euclid(self.edge.y - 1),
+                    _ => 1 + (*y as i64 - 1 - round as i64).rem_euclid(self.edge.y - 1),
+                };
+                (Pos { x: x as i64, y }, *direct)
+            })
+        });
+
+        h_blizz.chain(v_blizz).collect()
+    }
 
-        if self.is_walkable(from_pos, round) {
-            res.push(from_pos);
+    fn at(&self, round: usize) -> GroveState<'_> {
+        let mut blizzards: HashMap<Pos, Vec<Direct>> = HashMap::new();
+        for (pos, direct) in self.blizzards_at(round) {
+            blizzards.entry(pos).or_default().push(direct);
+        }
+
+        GroveState {
+            grove: self,
+            blizzards,
         }
-        res
     }
 
-    fn print(&self) {
-        todo!();
+    fn trace(&self, round: usize) {
+        trace!("\n{}", self.at(round).render());
     }
 }
 
-fn find_shortest_path(grove: &Grove, start_step: usize, max_rounds: usize) -> Option<usize> {
-    let mut jobs: VecDeque<(Pos, usize)> = VecDeque::new();
-    for next_pos in grove.next_poses(grove.start, start_step + 1) {
-        jobs.push_front((next_pos, start_step + 1));
+struct GroveState<'a> {
+    grove: &'a Grove,
+    blizzards: HashMap<Pos, Vec<Direct>>,
+}
+
+impl Render for GroveState<'_> {
+    fn viewport(&self) -> Option<Viewport> {
+        Some(Viewport::new(
+            0,
+            0,
+            self.grove.edge.x + 1,
+            self.grove.edge.y,
+        ))
     }
-    let mut processed: HashSet<(Pos, usize)> = HashSet::new();
 
-    let mut best_res = None;
+    fn cell(&self, x: i64, y: i64) -> char {
+        let pos = Pos { x, y };
+        if pos == self.grove.start || pos == self.grove.goal {
+            return '.';
+        }
+        if x == 0 || y == 0 || x == self.grove.edge.x + 1 || y == self.grove.edge.y {
+            return '#';
+        }
 
-    while let Some((pos, steps)) = jobs.pop_front() {
-        if pos == grove.goal && (best_res.is_none() || steps < best_res.unwrap()) {
-            println!("found res steps={steps}");
-            best_res = Some(steps);
-            continue;
+        match self.blizzards.get(&pos).map(|b| b.as_slice()) {
+            None | Some([]) => '.',
+            Some([direct]) => direct.to_char(),
+            Some(directs) => char::from_digit(directs.len() as u32, 10).unwrap_or('*'),
         }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Waypoint {
+    Start,
+    Goal,
+}
 
-        if steps - start_step >= max_rounds || (best_res.is_some() && steps >= best_res.unwrap()) {
-            continue;
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trip {
+    pub legs: Vec<usize>,
+    pub route: Vec<(usize, Pos)>,
+}
+
+impl Trip {
+    pub fn duration(&self) -> usize {
+        self.legs.iter().sum()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Unreachable {
+    pub leg: usize,
+    pub from: Waypoint,
+    pub to: Waypoint,
+    pub departure: usize,
+}
+
+impl fmt::Display for Unreachable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "leg {} from {:?} to {:?} departing at minute {} is unreachable",
+            self.leg + 1,
+            self.from,
+            self.to,
+            self.departure
+        )
+    }
+}
+
+// timed route from `from` to `to`, including both ends; None once every
+// (position, time in period) state has been visited without reaching `to`
+fn find_shortest_path(
+    grove: &Grove,
+    from: Pos,
+    to: Pos,
+    start_step: usize,
+) -> Option<Vec<(usize, Pos)>> {
+    let mut nodes: Vec<(Pos, usize, Option<usize>)> = vec![(from, start_step, None)];
+    let mut jobs: VecDeque<usize> = VecDeque::from([0]);
+    let mut processed: HashSet<(Pos, usize)> = HashSet::from([(from, start_step % grove.period)]);
+
+    // breadth first, so the goal is reached with the least steps first
+    while let Some(idx) = jobs.pop_front() {
+        let (pos, steps, _) = nodes[idx];
+        if pos == to {
+            debug!("found res steps={steps}");
+            let mut route = vec![];
+            let mut cur = Some(idx);
+            while let Some(idx) = cur {
+                route.push((nodes[idx].1, nodes[idx].0));
+                cur = nodes[idx].2;
+            }
+            route.reverse();
+            return Some(route);
         }
 
         for next_pos in grove.next_poses(pos, steps + 1) {
-            let job = (next_pos, steps + 1);
-            if !processed.contains(&job) {
-                jobs.push_front(job);
-                processed.insert(job);
+            if processed.insert((next_pos, (steps + 1) % grove.period)) {
+                nodes.push((next_pos, steps + 1, Some(idx)));
+                jobs.push_back(nodes.len() - 1);
             }
         }
     }
 
-    best_res
+    None
 }
 
-pub fn solve_a(loader: &DataLoader) -> Result<String, &str> {
-    let grove = Grove::from_loader(loader);
-    println!("Grove={grove:?}");
-    let max_rounds = (grove.goal.x + grove.goal.y) * 2;
-    let steps = find_shortest_path(&grove, 0, max_rounds as usize).ok_or("no path found")?;
-    Ok(steps.to_string())
+fn plan(grove: &Grove, waypoints: &[Waypoint]) -> Result<Trip, Unreachable> {
+    let mut trip = Trip {
+        legs: vec![],
+        route: vec![],
+    };
+    let mut steps = 0;
+    for (leg, pair) in waypoints.windows(2).enumerate() {
+        let (from, to) = (pair[0], pair[1]);
+        let route = find_shortest_path(grove, grove.waypoint(from), grove.waypoint(to), steps)
+            .ok_or(Unreachable {
+                leg,
+                from,
+                to,
+                departure: steps,
+            })?;
+        let arrival = route.last().map_or(steps, |(t, _)| *t);
+        trip.legs.push(arrival - steps);
+        let skip = usize::from(!trip.route.is_empty());
+        trip.route.extend(route.into_iter().skip(skip));
+        steps = arrival;
+    }
+    Ok(trip)
 }
 
-pub fn solve_b(loader: &DataLoader) -> Result<String, &str> {
+pub fn plan_trip(loader: &DataLoader, waypoints: &[Waypoint]) -> Result<Trip, Unreachable> {
     let grove = Grove::from_loader(loader);
-    println!("grove={grove:?}");
-
-    let mut inverted_grove = grove.clone();
-    inverted_grove.start = grove.goal;
-    inverted_grove.goal = grove.start;
-    inverted_grove.start_direction = grove.start_direction.opposite();
-    inverted_grove.goal_direction = grove.goal_direction.opposite();
+    trace!("grove={grove:?}");
+    plan(&grove, waypoints)
+}
 
-    println!("inverted_grove={inverted_grove:?}");
+pub fn solve_a(loader: &DataLoader) -> Result<String, &str> {
+    let trip = plan_trip(loader, &[Waypoint::Start, Waypoint::Goal]).map_err(|err| {
+        debug!("{err}");
+        "no path found"
+    })?;
+    Ok(trip.duration().to_string())
+}
 
-    let max_rounds = (grove.goal.x + grove.goal.y) * 3;
-    let to_steps_1 =
-        find_shortest_path(&grove, 0, max_rounds as usize).ok_or("to_steps_1 not found")?;
-    let back_steps = find_shortest_path(&inverted_grove, to_steps_1, max_rounds as usize)
-        .ok_or("back_steps not found")?;
-    let to_steps_2 = find_shortest_path(&grove, back_steps, max_rounds as usize)
-        .ok_or("to_steps_2 not found")?;
+pub fn animate(loader: &DataLoader, rounds: usize) -> Result<Animation, &str> {
+    let grove = Grove::from_loader(loader);
+    let mut animation = Animation::new(Renderer::new());
+    for round in 0..=rounds {
+        animation.push(&grove.at(round));
+    }
+    Ok(animation)
+}
 
-    Ok(to_steps_2.to_string())
+pub fn solve_b(loader: &DataLoader) -> Result<String, &str> {
+    let waypoints = [
+        Waypoint::Start,
+        Waypoint::Goal,
+        Waypoint::Start,
+        Waypoint::Goal,
+    ];
+    let trip = plan_trip(loader, &waypoints).map_err(|err| {
+        debug!("{err}");
+        "no path found"
+    })?;
+    debug!("legs={:?}", trip.legs);
+    Ok(trip.duration().to_string())
 }
 
 #[cfg(test)]
@@ -257,15 +442,13 @@ mod solver_test {
     #[test]
     fn test_h_blizz_direct_east() {
         let h_blizz = vec![vec![], vec![(1, Direct::E)]];
-        let grove = Grove {
-            start: Pos { x: 0, y: 0 },
-            start_direction: Direct::N,
-            goal: Pos { x: 5, y: 6 },
-            goal_direction: Direct::N,
-            edge: Pos { x: 5, y: 6 },
-            v_blizz: vec![vec![], vec![], vec![], vec![], vec![], vec![], vec![]],
+        let grove = Grove::new(
+            Pos { x: 0, y: 0 },
+            Pos { x: 5, y: 6 },
+            Pos { x: 5, y: 6 },
             h_blizz,
-        };
+            vec![vec![], vec![], vec![], vec![], vec![], vec![], vec![]],
+        );
         assert!(!grove.is_walkable(Pos { x: 1, y: 1 }, 0));
         assert!(grove.is_walkable(Pos { x: 1, y: 1 }, 1));
         assert!(grove.is_walkable(Pos { x: 1, y: 1 }, 2));
@@ -282,15 +465,13 @@ mod solver_test {
     #[test]
     fn test_h_blizz_direct_west() {
         let h_blizz = vec![vec![], vec![(1, Direct::W)]];
-        let grove = Grove {
-            start: Pos { x: 0, y: 0 },
-            start_direction: Direct::N,
-            goal: Pos { x: 5, y: 6 },
-            goal_direction: Direct::N,
-            edge: Pos { x: 5, y: 6 },
-            v_blizz: vec![vec![], vec![], vec![], vec![], vec![], vec![], vec![]],
+        let grove = Grove::new(
+            Pos { x: 0, y: 0 },
+            Pos { x: 5, y: 6 },
+            Pos { x: 5, y: 6 },
             h_blizz,
-        };
+            vec![vec![], vec![], vec![], vec![], vec![], vec![], vec![]],
+        );
         assert!(!grove.is_walkable(Pos { x: 1, y: 1 }, 0));
         assert!(grove.is_walkable(Pos { x: 1, y: 1 }, 1));
 
@@ -301,15 +482,13 @@ mod solver_test {
     #[test]
     fn test_v_blizz_direct_south() {
         let v_blizz = vec![vec![], vec![(1, Direct::S)]];
-        let grove = Grove {
-            start: Pos { x: 0, y: 0 },
-            start_direction: Direct::N,
-            goal: Pos { x: 5, y: 6 },
-            goal_direction: Direct::N,
-            edge: Pos { x: 5, y: 6 },
-            h_blizz: vec![vec![], vec![], vec![], vec![], vec![], vec![], vec![]],
+        let grove = Grove::new(
+            Pos { x: 0, y: 0 },
+            Pos { x: 5, y: 6 },
+            Pos { x: 5, y: 6 },
+            vec![vec![], vec![], vec![], vec![], vec![], vec![], vec![]],
             v_blizz,
-        };
+        );
         assert!(!grove.is_walkable(Pos { x: 1, y: 1 }, 0));
         assert!(grove.is_walkable(Pos { x: 1, y: 1 }, 1));
 
@@ -321,18 +500,77 @@ mod solver_test {
     #[test]
     fn test_v_blizz_direct_north() {
         let v_blizz = vec![vec![], vec![(1, Direct::N)]];
-        let grove = Grove {
-            start: Pos { x: 0, y: 0 },
-            start_direction: Direct::N,
-            goal: Pos { x: 5, y: 6 },
-            goal_direction: Direct::N,
-            edge: Pos { x: 5, y: 6 },
-            h_blizz: vec![vec![], vec![], vec![], vec![], vec![], vec![], vec![]],
+        let grove = Grove::new(
+            Pos { x: 0, y: 0 },
+            Pos { x: 5, y: 6 },
+            Pos { x: 5, y: 6 },
+            vec![vec![], vec![], vec![], vec![], vec![], vec![], vec![]],
             v_blizz,
-        };
+        );
         assert!(!grove.is_walkable(Pos { x: 1, y: 1 }, 0));
         assert!(grove.is_walkable(Pos { x: 1, y: 1 }, 1));
 
         assert!(!grove.is_walkable(Pos { x: 1, y: 5 }, 1));
     }
+
+    #[test]
+    fn test_period() {
+        let grove = Grove::from_loader(&DataLoader::from_file("data/test_input.txt"));
+        assert_eq!(grove.period, 12);
+
+        let state = grove.at(3);
+        for y in 1..grove.edge.y {
+            for x in 1..=grove.edge.x {
+                let pos = Pos { x, y };
+                assert_eq!(grove.is_walkable(pos, 3), state.cell(x, y) == '.');
+                assert_eq!(grove.is_walkable(pos, 3), grove.is_walkable(pos, 15));
+            }
+        }
+    }
+
+    #[test]
+    fn test_trip() {
+        let grove = Grove::from_loader(&DataLoader::from_file("data/test_input.txt"));
+        let waypoints = [
+            Waypoint::Start,
+            Waypoint::Goal,
+            Waypoint::Start,
+            Waypoint::Goal,
+        ];
+        let trip = plan(&grove, &waypoints).unwrap();
+        assert_eq!(trip.legs, vec![18, 23, 13]);
+        assert_eq!(trip.route.len(), 55);
+        assert_eq!(trip.route[0], (0, grove.start));
+        assert_eq!(trip.route[18], (18, grove.goal));
+        assert_eq!(trip.route[41], (41, grove.start));
+        assert_eq!(trip.route[54], (54, grove.goal));
+        for (step, (time, pos)) in trip.route.iter().enumerate().skip(1) {
+            let (_, prev) = trip.route[step - 1];
+            assert_eq!(*time, step);
+            assert!((pos.x - prev.x).abs() + (pos.y - prev.y).abs() <= 1);
+            assert!(grove.is_walkable(*pos, *time));
+        }
+    }
+
+    #[test]
+    fn test_trip_unreachable() {
+        // a single row valley where the blizzard never leaves the cell above the goal
+        let grove = Grove::new(
+            Pos { x: 1, y: 0 },
+            Pos { x: 3, y: 2 },
+            Pos { x: 3, y: 2 },
+            vec![vec![], vec![]],
+            vec![vec![], vec![], vec![], vec![(1, Direct::S)]],
+        );
+        assert_eq!(
+            plan(&grove, &[Waypoint::Start, Waypoint::Goal]),
+            Err(Unreachable {
+                leg: 0,
+                from: Waypoint::Start,
+                to: Waypoint::Goal,
+                departure: 0,
+            })
+        );
+        assert_eq!(plan(&grove, &[Waypoint::Start]).unwrap().legs, vec![]);
+    }
 }